@@ -0,0 +1,198 @@
+//! Compiles the RLE and plaintext patterns in `patterns/` into a table for
+//!  `src/library.rs`, with each pattern's cells packed a bit each, so that
+//!  adding a demo pattern is just a matter of adding its file.
+//!
+//! The build script can't use the library it's building, so it has a parser of
+//!  its own. Only whether cells are alive is kept, and anything it can't make
+//!  sense of fails the build.
+//!
+//! It also turns the `log-*` features into a `DEFMT_LOG` for defmt, which only
+//!  reads its log level from the environment.
+
+use std::{env, fmt::Write, fs, path::Path};
+
+
+/// The side of the firmware's world, which no pattern may be bigger than.
+const WORLD: usize = 32;
+
+struct Pattern {
+    name: String,
+    width: usize,
+    height: usize,
+    rule: Option<String>,
+    /// Whether each cell is alive, in reading order.
+    cells: Vec<bool>,
+}
+
+impl Pattern {
+    fn new(name: String, width: usize, height: usize, rule: Option<String>) -> Result<Self, String> {
+        if width > WORLD || height > WORLD {
+            return Err(format!("{width}x{height} is bigger than the {WORLD}x{WORLD} world"));
+        }
+        Ok(Pattern { name, width, height, rule, cells: vec![false; width * height] })
+    }
+
+    fn set(&mut self, row: usize, col: usize) {
+        if row < self.height && col < self.width {
+            self.cells[row * self.width + col] = true;
+        }
+    }
+
+    /// The cells eight to a byte, least significant bit first.
+    fn bytes(&self) -> Vec<u8> {
+        self.cells.chunks(8).map(|bits| bits.iter().rev().fold(0, |byte, bit| byte << 1 | *bit as u8)).collect()
+    }
+}
+
+
+fn parse_rle(stem: &str, text: &str) -> Result<Pattern, String> {
+    let mut name = stem.to_string();
+    let mut lines = text.lines().map(str::trim);
+
+    let header = loop {
+        let line = lines.next().ok_or("no header line")?;
+        if let Some(comment) = line.strip_prefix("#N") {
+            name = comment.trim().to_string();
+        } else if line.starts_with('x') {
+            break line;
+        } else if !line.is_empty() && !line.starts_with('#') {
+            return Err(format!("expected a header, not {line:?}"));
+        }
+    };
+
+    let (sizes, rule) = match header.find("rule") {
+        Some(at) => (&header[..at], Some(&header[at..])),
+        None => (header, None),
+    };
+    let size = |key: &str| -> Result<usize, String> {
+        sizes
+            .split(',')
+            .filter_map(|part| part.split_once('='))
+            .find(|(k, _)| k.trim() == key)
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .ok_or(format!("no {key} in {header:?}"))
+    };
+    let rule = rule
+        .and_then(|rule| rule.split_once('='))
+        .map(|(_, rule)| rule.split(':').next().unwrap_or(rule).trim().to_string());
+
+    let mut pattern = Pattern::new(name, size("x")?, size("y")?, rule)?;
+    let (mut row, mut col, mut count) = (0, 0, None::<usize>);
+
+    for byte in lines.collect::<String>().bytes() {
+        // Skipped before the count is taken, as `src/rle.rs` does, so that a
+        //  space can't run into the digits after it.
+        if byte.is_ascii_whitespace() {
+            continue;
+        }
+        if byte.is_ascii_digit() {
+            count = Some(count.unwrap_or(0) * 10 + (byte - b'0') as usize);
+            continue;
+        }
+
+        let run = count.take().unwrap_or(1);
+        match byte {
+            b'b' | b'.' => col += run,
+            b'o' | b'A'..=b'X' => {
+                for _ in 0..run {
+                    pattern.set(row, col);
+                    col += 1;
+                }
+            },
+            // The prefixes of states past 24, which are all alive.
+            b'p'..=b'y' => count = Some(run),
+            b'$' => {
+                row += run;
+                col = 0;
+            },
+            b'!' => return Ok(pattern),
+            _ => return Err(format!("unexpected {:?}", byte as char)),
+        }
+    }
+
+    Ok(pattern)
+}
+
+fn parse_cells(stem: &str, text: &str) -> Result<Pattern, String> {
+    let name = text
+        .lines()
+        .find_map(|line| line.strip_prefix("!Name:"))
+        .map_or(stem.to_string(), |name| name.trim().to_string());
+    let rows = text.lines().filter(|line| !line.starts_with('!')).map(str::trim_end).collect::<Vec<_>>();
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut pattern = Pattern::new(name, width, rows.len(), None)?;
+
+    for (row, line) in rows.iter().enumerate() {
+        for (col, byte) in line.bytes().enumerate() {
+            match byte {
+                b'O' | b'*' => pattern.set(row, col),
+                b'.' => {},
+                _ => return Err(format!("unexpected {:?}", byte as char)),
+            }
+        }
+    }
+
+    Ok(pattern)
+}
+
+
+/// The level of the most detailed `log-*` feature, unless `DEFMT_LOG` has been
+///  set by hand.
+fn log_level() -> Option<&'static str> {
+    println!("cargo::rerun-if-env-changed=DEFMT_LOG");
+    if env::var_os("DEFMT_LOG").is_some() {
+        return None;
+    }
+
+    ["trace", "debug", "info", "warn", "error"]
+        .into_iter()
+        .find(|level| env::var_os(format!("CARGO_FEATURE_LOG_{}", level.to_uppercase())).is_some())
+}
+
+
+fn main() {
+    if let Some(level) = log_level() {
+        println!("cargo::rustc-env=DEFMT_LOG={level}");
+    }
+
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("patterns");
+    println!("cargo::rerun-if-changed={}", dir.display());
+
+    let mut files = fs::read_dir(&dir)
+        .map(|entries| entries.map(|entry| entry.unwrap().path()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    // The same order on every machine, which the library's tests rely on.
+    files.sort();
+
+    let mut table = String::from("[\n");
+    for path in files {
+        let stem = path.file_stem().unwrap().to_string_lossy();
+        let parse = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("rle") => parse_rle,
+            Some("cells") => parse_cells,
+            _ => continue,
+        };
+
+        let text = fs::read_to_string(&path).unwrap();
+        let pattern = parse(&stem, &text).unwrap_or_else(|error| panic!("{}: {error}", path.display()));
+
+        let rule = match &pattern.rule {
+            Some(rule) => format!("Some(Generations::new({rule:?}))"),
+            None => "None".to_string(),
+        };
+        writeln!(
+            table,
+            "    Entry {{ name: {:?}, width: {}, height: {}, rule: {rule}, cells: &{:?} }},",
+            pattern.name,
+            pattern.width,
+            pattern.height,
+            pattern.bytes(),
+        )
+        .unwrap();
+    }
+    table.push(']');
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("patterns.rs");
+    fs::write(out, table).unwrap();
+}
@@ -1,5 +1,11 @@
 MEMORY
 {
-  FLASH : ORIGIN = 0x00000000, LENGTH = 256K
+  FLASH : ORIGIN = 0x00000000, LENGTH = 244K
+  /* The settings and saved worlds, kept across resets: twelve 1K pages at
+     the top of flash, which the firmware never erases otherwise. */
+  STORAGE : ORIGIN = 0x0003D000, LENGTH = 12K
   RAM   : ORIGIN = 0x20000000, LENGTH = 16K
-}
\ No newline at end of file
+}
+
+_storage_start = ORIGIN(STORAGE);
+_storage_end = ORIGIN(STORAGE) + LENGTH(STORAGE);
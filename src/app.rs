@@ -0,0 +1,577 @@
+//! What the buttons do, as a state machine over the firmware's modes.
+//!
+//! The machine only decides what should happen; the firmware carries it out
+//!  through [`Effects`], which lets the transitions be tested on the host.
+
+use core::mem;
+
+use crate::{
+    automaton::Automaton,
+    editor::Cursor,
+    input::{Button, Event},
+    library::{Entry, PATTERNS},
+    options::{Setting, Speed},
+    soup::Soup,
+    stats::Statistic,
+    storage::{Slot, SAVED_SLOTS},
+};
+
+
+/// What the firmware is doing, and so what the buttons do.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum Mode {
+    Running,
+    Paused,
+    /// Running, while A and B move the viewport.
+    Panning,
+    /// Paused, with a cursor to draw cells with.
+    Editing(Cursor),
+    /// Paused, showing one of the world's statistics.
+    Stats(Statistic),
+    /// Paused, picking which setting to change.
+    Options(Setting),
+    /// Changing a setting, which takes effect straight away.
+    Choosing(Setting),
+    /// Paused, picking one of the saved worlds to load or save over.
+    Slots(usize),
+    /// Paused on the `_`th of the demo patterns in [`PATTERNS`].
+    Library(usize),
+    /// Choosing what to run, showing the icon of the `_`th choice.
+    Menu(usize),
+    /// Trying out soups for the `_`th choice before running it, with a new one
+    ///  shown whenever the settings change.
+    Settings(usize),
+}
+
+impl Mode {
+    /// Whether the world is being stepped.
+    pub const fn is_running(self) -> bool {
+        matches!(self, Mode::Running | Mode::Panning)
+    }
+}
+
+
+/// Everything the state machine can ask of the firmware.
+pub trait Effects {
+    /// Starts or stops stepping the world.
+    fn run(&mut self, running: bool);
+
+    /// Redraws the world, replacing whatever else was on the display.
+    fn show_world(&mut self);
+
+    fn show_icon(&mut self, automaton: &Automaton);
+
+    fn show_statistic(&mut self, statistic: Statistic);
+
+    /// Shows the name of `setting`.
+    fn show_setting(&mut self, setting: Setting);
+
+    /// Shows what `setting` is set to now.
+    fn show_value(&mut self, setting: Setting);
+
+    /// Shows the editing cursor, or hides it for `None`.
+    fn set_cursor(&mut self, cursor: Option<Cursor>);
+
+    /// Switches to running `automaton`, starting from its first world.
+    fn start(&mut self, automaton: Automaton);
+
+    /// Replaces the world with the next fresh one.
+    fn reseed(&mut self);
+
+    /// Moves the world on by one generation.
+    fn step(&mut self);
+
+    fn pan(&mut self, rows: isize, cols: isize);
+
+    /// Changes the cell under `cursor` to the next state worth drawing with.
+    fn toggle(&mut self, cursor: Cursor);
+
+    fn change_soup(&mut self, change: fn(Soup) -> Soup);
+
+    /// Changes how fast the world runs, straight away.
+    fn change_speed(&mut self, change: fn(Speed) -> Speed);
+
+    /// Moves `setting` on to its next value.
+    fn change(&mut self, setting: Setting);
+
+    /// Shows what's in the `_`th saved slot.
+    fn show_slot(&mut self, slot: usize);
+
+    /// Keeps the world in `slot`, to load again after a reset.
+    fn save(&mut self, slot: Slot);
+
+    /// Replaces the world with the one kept in `slot`, if there is one.
+    fn load(&mut self, slot: Slot);
+
+    /// Replaces the world with a demo pattern, switching to its rule if it
+    ///  has one, and shows its name.
+    fn place(&mut self, pattern: &'static Entry);
+}
+
+
+/// The state machine itself: the current mode and the automata the menu
+///  chooses between.
+#[derive(Clone, Copy, Debug)]
+pub struct App {
+    mode: Mode,
+    choices: &'static [Automaton],
+}
+
+impl App {
+    /// A machine starting in the menu, on the first of `choices`.
+    pub const fn new(choices: &'static [Automaton]) -> Self {
+        App { mode: Mode::Menu(0), choices }
+    }
+
+    /// A machine starting paused, for picking up where the board left off.
+    pub const fn resumed(choices: &'static [Automaton]) -> Self {
+        App { mode: Mode::Paused, choices }
+    }
+
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Enters the first mode, for when the firmware has just started.
+    pub fn start(&self, effects: &mut impl Effects) {
+        self.enter(effects);
+    }
+
+    /// Moves on from `event`. Changing to a different kind of mode leaves the
+    ///  old one and enters the new one, while changes within a mode, such as
+    ///  moving the cursor, just take effect.
+    pub fn handle(&mut self, event: Event, effects: &mut impl Effects) {
+        let next = self.transition(event, effects);
+        self.switch(next, effects);
+    }
+
+    /// Pauses the world from whatever mode, as A does while running, for the
+    ///  serial console.
+    pub fn pause(&mut self, effects: &mut impl Effects) {
+        if self.mode.is_running() {
+            effects.save(Slot::Last);
+        }
+        self.switch(Mode::Paused, effects);
+    }
+
+    /// Runs the world from whatever mode, for the serial console.
+    pub fn resume(&mut self, effects: &mut impl Effects) {
+        if !self.mode.is_running() {
+            self.switch(Mode::Running, effects);
+        }
+    }
+
+    fn switch(&mut self, next: Mode, effects: &mut impl Effects) {
+        if mem::discriminant(&next) != mem::discriminant(&self.mode) {
+            self.exit(effects);
+            self.mode = next;
+            self.enter(effects);
+        } else {
+            self.mode = next;
+        }
+    }
+
+    /// Where `event` goes from the current mode, doing whatever it asks for on
+    ///  the way. Events that mean nothing in a mode leave it as it is.
+    fn transition(&self, event: Event, effects: &mut impl Effects) -> Mode {
+        const A: Event = Event::Release(Button::A);
+        const B: Event = Event::Release(Button::B);
+
+        match (self.mode, event) {
+            // Both buttons together toggle panning. When paused they show the
+            //  statistics instead, then the options and then the menu, and in
+            //  the menu they go on to the soup settings for the chosen mode,
+            //  if it has any. Changing an option goes back to running with it.
+            //  In the editor they close it again.
+            (Mode::Running, Event::Chord) => Mode::Panning,
+            (Mode::Panning, Event::Chord) => Mode::Running,
+            (Mode::Paused, Event::Chord) => Mode::Stats(Statistic::default()),
+            (Mode::Editing(_), Event::Chord) => Mode::Paused,
+            (Mode::Stats(_), Event::Chord) => Mode::Options(Setting::default()),
+            (Mode::Options(_), Event::Chord) => Mode::Menu(0),
+            (Mode::Choosing(_), Event::Chord) => Mode::Running,
+            (Mode::Menu(choice), Event::Chord) => match self.choices[choice] {
+                Automaton::Generations(_) => Mode::Settings(choice),
+                _ => Mode::Menu(choice),
+            },
+            (Mode::Settings(_), Event::Chord) => Mode::Running,
+
+            (Mode::Running, A) => {
+                effects.save(Slot::Last);
+                Mode::Paused
+            },
+            (Mode::Running, B) => {
+                effects.reseed();
+                Mode::Running
+            },
+            (Mode::Running, Event::LongPress(Button::A)) => {
+                effects.change_speed(Speed::slower);
+                Mode::Running
+            },
+            (Mode::Running, Event::LongPress(Button::B)) => {
+                effects.change_speed(Speed::faster);
+                Mode::Running
+            },
+
+            (Mode::Paused, A) => Mode::Running,
+            (Mode::Paused, B) => {
+                effects.step();
+                Mode::Paused
+            },
+            (Mode::Paused, Event::LongPress(Button::A)) => Mode::Slots(0),
+            (Mode::Paused, Event::LongPress(Button::B)) => Mode::Editing(Cursor::default()),
+
+            (Mode::Library(pattern), A) => {
+                let next = (pattern + 1) % PATTERNS.len();
+                effects.place(&PATTERNS[next]);
+                Mode::Library(next)
+            },
+            (Mode::Library(_), B) => Mode::Running,
+            (Mode::Library(_), Event::Chord) => Mode::Paused,
+
+            (Mode::Slots(slot), A) => {
+                let next = (slot + 1) % SAVED_SLOTS;
+                effects.show_slot(next);
+                Mode::Slots(next)
+            },
+            (Mode::Slots(slot), B) => {
+                effects.load(Slot::Saved(slot));
+                Mode::Paused
+            },
+            (Mode::Slots(slot), Event::LongPress(Button::B)) => {
+                effects.save(Slot::Saved(slot));
+                Mode::Paused
+            },
+            // Both buttons together go on from the slots to the library,
+            //  when there's anything in it.
+            (Mode::Slots(_), Event::Chord) if !PATTERNS.is_empty() => Mode::Library(0),
+            (Mode::Slots(_), Event::Chord) => Mode::Paused,
+
+            (Mode::Panning, A) => {
+                effects.pan(0, 1);
+                Mode::Panning
+            },
+            (Mode::Panning, B) => {
+                effects.pan(1, 0);
+                Mode::Panning
+            },
+
+            (Mode::Editing(mut cursor), A) => {
+                cursor.advance();
+                effects.set_cursor(Some(cursor));
+                Mode::Editing(cursor)
+            },
+            (Mode::Editing(cursor), B) => {
+                effects.toggle(cursor);
+                Mode::Editing(cursor)
+            },
+
+            (Mode::Stats(statistic), A) => {
+                effects.show_statistic(statistic.next());
+                Mode::Stats(statistic.next())
+            },
+            (Mode::Stats(_), B) => Mode::Paused,
+
+            (Mode::Options(setting), A) => {
+                effects.show_setting(setting.next());
+                Mode::Options(setting.next())
+            },
+            (Mode::Options(setting), B) => Mode::Choosing(setting),
+
+            (Mode::Choosing(setting), A) => {
+                effects.change(setting);
+                effects.show_value(setting);
+                Mode::Choosing(setting)
+            },
+            (Mode::Choosing(setting), B) => Mode::Options(setting),
+
+            (Mode::Menu(choice), A) => {
+                let next = (choice + 1) % self.choices.len();
+                effects.show_icon(&self.choices[next]);
+                Mode::Menu(next)
+            },
+            (Mode::Menu(choice), B) => {
+                effects.start(self.choices[choice]);
+                Mode::Running
+            },
+
+            (Mode::Settings(choice), A | B) => {
+                if event == A {
+                    effects.change_soup(Soup::denser);
+                } else {
+                    effects.change_soup(|soup| Soup { pattern: soup.pattern.next(), ..soup });
+                }
+                effects.reseed();
+                Mode::Settings(choice)
+            },
+
+            (mode, _) => mode,
+        }
+    }
+
+    fn enter(&self, effects: &mut impl Effects) {
+        match self.mode {
+            Mode::Running | Mode::Panning => effects.run(true),
+            Mode::Paused => {
+                effects.run(false);
+                effects.show_world();
+            },
+            Mode::Editing(cursor) => effects.set_cursor(Some(cursor)),
+            Mode::Stats(statistic) => effects.show_statistic(statistic),
+            Mode::Options(setting) => effects.show_setting(setting),
+            Mode::Choosing(setting) => effects.show_value(setting),
+            Mode::Menu(choice) => {
+                effects.run(false);
+                effects.show_icon(&self.choices[choice]);
+            },
+            Mode::Settings(choice) => effects.start(self.choices[choice]),
+            Mode::Slots(slot) => effects.show_slot(slot),
+            Mode::Library(pattern) => effects.place(&PATTERNS[pattern]),
+        }
+    }
+
+    /// Leaving the editor keeps what was drawn, as the last world.
+    fn exit(&self, effects: &mut impl Effects) {
+        if let Mode::Editing(_) = self.mode {
+            effects.set_cursor(None);
+            effects.save(Slot::Last);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{elementary::RULE_30, generations::Generations, rule};
+
+    const CHOICES: [Automaton; 3] = [
+        Automaton::Generations(Generations::from_rule(rule::LIFE)),
+        Automaton::Wireworld,
+        Automaton::Elementary(RULE_30),
+    ];
+
+    /// Writes down everything asked of it.
+    #[derive(Default)]
+    struct Recorder {
+        effects: Vec<String>,
+        soup: Soup,
+        speed: Speed,
+    }
+
+    impl Effects for Recorder {
+        fn run(&mut self, running: bool) { self.effects.push(format!("run {running}")); }
+        fn show_world(&mut self) { self.effects.push("show world".into()); }
+        fn show_icon(&mut self, automaton: &Automaton) { self.effects.push(format!("icon {automaton:?}")); }
+        fn show_statistic(&mut self, statistic: Statistic) { self.effects.push(format!("show {statistic:?}")); }
+        fn set_cursor(&mut self, cursor: Option<Cursor>) { self.effects.push(format!("cursor {cursor:?}")); }
+        fn start(&mut self, automaton: Automaton) { self.effects.push(format!("start {automaton:?}")); }
+        fn reseed(&mut self) { self.effects.push("reseed".into()); }
+        fn step(&mut self) { self.effects.push("step".into()); }
+        fn pan(&mut self, rows: isize, cols: isize) { self.effects.push(format!("pan {rows} {cols}")); }
+        fn toggle(&mut self, cursor: Cursor) { self.effects.push(format!("toggle {cursor:?}")); }
+        fn change_soup(&mut self, change: fn(Soup) -> Soup) { self.soup = change(self.soup); }
+        fn change_speed(&mut self, change: fn(Speed) -> Speed) { self.speed = change(self.speed); }
+        fn show_setting(&mut self, setting: Setting) { self.effects.push(format!("setting {setting:?}")); }
+        fn show_value(&mut self, setting: Setting) { self.effects.push(format!("value {setting:?}")); }
+        fn change(&mut self, setting: Setting) { self.effects.push(format!("change {setting:?}")); }
+        fn show_slot(&mut self, slot: usize) { self.effects.push(format!("slot {slot}")); }
+        fn save(&mut self, slot: Slot) { self.effects.push(format!("save {slot:?}")); }
+        fn load(&mut self, slot: Slot) { self.effects.push(format!("load {slot:?}")); }
+        fn place(&mut self, pattern: &'static Entry) { self.effects.push(format!("place {}", pattern.name)); }
+    }
+
+    const A: Event = Event::Release(Button::A);
+    const B: Event = Event::Release(Button::B);
+
+    fn run(events: &[Event]) -> (App, Recorder) {
+        let mut app = App::new(&CHOICES);
+        let mut recorder = Recorder::default();
+        app.start(&mut recorder);
+
+        for event in events {
+            app.handle(*event, &mut recorder);
+        }
+
+        (app, recorder)
+    }
+
+    #[test]
+    fn starts_in_the_menu() {
+        let (app, recorder) = run(&[]);
+
+        assert_eq!(app.mode(), Mode::Menu(0));
+        assert_eq!(recorder.effects, ["run false", &format!("icon {:?}", CHOICES[0])]);
+    }
+
+    #[test]
+    fn menu_cycles_and_chooses() {
+        let (app, recorder) = run(&[A, A, A, A, B]);
+
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.effects[recorder.effects.len() - 2..], [format!("start {:?}", CHOICES[1]), "run true".into()]);
+    }
+
+    #[test]
+    fn pausing_and_stepping() {
+        let (app, recorder) = run(&[B, A, B, B]);
+
+        assert_eq!(app.mode(), Mode::Paused);
+        assert_eq!(recorder.effects[4..], ["save Last", "run false", "show world", "step", "step"]);
+    }
+
+    #[test]
+    fn the_console_pauses_and_resumes_from_anywhere() {
+        let (mut app, mut recorder) = run(&[B, A, Event::LongPress(Button::B)]);
+        app.pause(&mut recorder);
+        assert_eq!(app.mode(), Mode::Paused);
+        assert_eq!(recorder.effects[recorder.effects.len() - 4..], ["cursor None", "save Last", "run false", "show world"]);
+
+        app.resume(&mut recorder);
+        app.resume(&mut recorder);
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.effects.last().unwrap(), "run true");
+        assert_eq!(recorder.effects.iter().filter(|effect| *effect == "run true").count(), 2);
+
+        app.pause(&mut recorder);
+        assert_eq!(recorder.effects[recorder.effects.len() - 3..], ["save Last", "run false", "show world"]);
+    }
+
+    #[test]
+    fn chords_go_round_the_paused_screens() {
+        let (app, recorder) = run(&[B, A, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Stats(Statistic::default()));
+        assert_eq!(recorder.effects.last().unwrap(), "show Generation");
+
+        let (app, recorder) = run(&[B, A, Event::Chord, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Options(Setting::Mode));
+        assert_eq!(recorder.effects.last().unwrap(), "setting Mode");
+
+        let (app, _) = run(&[B, A, Event::Chord, Event::Chord, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Menu(0));
+    }
+
+    #[test]
+    fn holding_b_opens_the_editor_and_both_close_it() {
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::B)]);
+        assert_eq!(app.mode(), Mode::Editing(Cursor::default()));
+        assert_eq!(recorder.effects.last().unwrap(), &format!("cursor {:?}", Some(Cursor::default())));
+
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::B), Event::Chord]);
+        assert_eq!(app.mode(), Mode::Paused);
+        // Leaving the editor hides the cursor and keeps what was drawn.
+        assert_eq!(recorder.effects[recorder.effects.len() - 4..], ["cursor None", "save Last", "run false", "show world"]);
+    }
+
+    #[test]
+    fn options_change_live_and_run_on() {
+        const OPTIONS: [Event; 4] = [B, A, Event::Chord, Event::Chord];
+
+        let (app, recorder) = run(&[&OPTIONS[..], &[A, A, B, A, A]].concat());
+        assert_eq!(app.mode(), Mode::Choosing(Setting::Speed));
+        assert_eq!(recorder.effects[recorder.effects.len() - 6..], [
+            "setting Speed",
+            "value Speed",
+            "change Speed",
+            "value Speed",
+            "change Speed",
+            "value Speed",
+        ]);
+
+        let (app, _) = run(&[&OPTIONS[..], &[B, B]].concat());
+        assert_eq!(app.mode(), Mode::Options(Setting::Mode));
+
+        let (app, recorder) = run(&[&OPTIONS[..], &[B, A, Event::Chord]].concat());
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.effects.last().unwrap(), "run true");
+    }
+
+    #[test]
+    fn moving_the_cursor_stays_in_the_editor() {
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::B), A, B]);
+
+        let moved = Cursor::new(0, 1);
+        assert_eq!(app.mode(), Mode::Editing(moved));
+        assert_eq!(recorder.effects[recorder.effects.len() - 2..], [format!("cursor {:?}", Some(moved)), format!("toggle {moved:?}")]);
+    }
+
+    #[test]
+    fn resuming_starts_paused() {
+        let mut app = App::resumed(&CHOICES);
+        let mut recorder = Recorder::default();
+        app.start(&mut recorder);
+        app.handle(A, &mut recorder);
+
+        assert_eq!(recorder.effects, ["run false", "show world", "run true"]);
+        assert_eq!(app.mode(), Mode::Running);
+    }
+
+    #[test]
+    fn slots_load_and_save() {
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::A), A, B]);
+        assert_eq!(app.mode(), Mode::Paused);
+        assert!(recorder.effects.contains(&"save Last".into()));
+        assert_eq!(recorder.effects[recorder.effects.len() - 5..], ["slot 0", "slot 1", "load Saved(1)", "run false", "show world"]);
+
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::A), A, A, A, A, Event::LongPress(Button::B)]);
+        assert_eq!(app.mode(), Mode::Paused);
+        assert!(recorder.effects.contains(&"save Saved(0)".into()));
+    }
+
+    #[test]
+    fn library_goes_through_the_patterns() {
+        let (app, recorder) = run(&[B, A, Event::LongPress(Button::A), Event::Chord, A, B]);
+
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.effects[recorder.effects.len() - 3..], [
+            format!("place {}", PATTERNS[0].name),
+            format!("place {}", PATTERNS[1].name),
+            "run true".into(),
+        ]);
+
+        let (app, _) = run(&[B, A, Event::LongPress(Button::A), Event::Chord, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Paused);
+    }
+
+    #[test]
+    fn long_presses_change_the_speed() {
+        let (app, recorder) = run(&[B, Event::LongPress(Button::B), Event::LongPress(Button::B), Event::LongPress(Button::B)]);
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.speed, Speed::Fastest);
+
+        let (_, recorder) = run(&[B, Event::LongPress(Button::A)]);
+        assert_eq!(recorder.speed, Speed::Slow);
+
+        // Only while running.
+        let (_, recorder) = run(&[Event::LongPress(Button::B)]);
+        assert_eq!(recorder.speed, Speed::default());
+    }
+
+    #[test]
+    fn panning_keeps_running() {
+        let (app, recorder) = run(&[B, Event::Chord, A, B, Event::Chord]);
+
+        assert_eq!(app.mode(), Mode::Running);
+        assert!(app.mode().is_running());
+        assert!(recorder.effects.contains(&"pan 0 1".into()));
+        assert!(recorder.effects.contains(&"pan 1 0".into()));
+    }
+
+    #[test]
+    fn only_soups_have_settings() {
+        let (app, recorder) = run(&[Event::Chord, A, A, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Running);
+        assert_eq!(recorder.soup, Soup::default().denser().denser());
+
+        let (app, _) = run(&[A, Event::Chord]);
+        assert_eq!(app.mode(), Mode::Menu(1));
+    }
+
+    #[test]
+    fn meaningless_events_change_nothing() {
+        let (app, recorder) = run(&[Event::Press(Button::A), Event::LongPress(Button::B), Event::DoubleClick(Button::A)]);
+
+        assert_eq!(app.mode(), Mode::Menu(0));
+        assert_eq!(recorder.effects.len(), 2);
+    }
+}
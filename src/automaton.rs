@@ -0,0 +1,127 @@
+//! The families of automata the firmware can run, behind one type.
+
+use core::fmt;
+
+use crate::{
+    boundary::Boundary,
+    elementary::{self, Elementary},
+    engine::update_automata,
+    generations::{self, Generations},
+    neighborhood::Neighborhood,
+    wireworld::wireworld_transitions,
+    world::World,
+};
+
+
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub enum Automaton {
+    /// Any Life-like or Generations rule.
+    Generations(Generations),
+    Wireworld,
+    /// A one-dimensional rule, run along the bottom row with its history
+    ///  scrolling up above it.
+    Elementary(Elementary),
+}
+
+impl Automaton {
+    /// Moves `world` on by one generation.
+    ///
+    /// Elementary rules only ever look along a row, so they ignore
+    ///  `neighborhood`.
+    pub fn step<C: World>(&self, world: &C, boundary: Boundary, neighborhood: Neighborhood) -> C {
+        match self {
+            Automaton::Generations(rule) => update_automata(world, boundary, neighborhood, *rule),
+            Automaton::Wireworld => update_automata(world, boundary, neighborhood, wireworld_transitions),
+            Automaton::Elementary(rule) => rule.update(world, boundary),
+        }
+    }
+
+    /// The same kind of automaton with the next of its preset rules, going
+    ///  back to the first after the last. Rules that aren't presets go to the
+    ///  first one too, and Wireworld has only the one rule.
+    pub fn next_rule(self) -> Automaton {
+        match self {
+            Automaton::Generations(rule) => {
+                let at = generations::PRESETS.iter().position(|(_, preset)| *preset == rule);
+                let next = at.map_or(0, |at| (at + 1) % generations::PRESETS.len());
+
+                Automaton::Generations(generations::PRESETS[next].1)
+            },
+            Automaton::Wireworld => Automaton::Wireworld,
+            Automaton::Elementary(rule) => {
+                let at = elementary::PRESETS.iter().position(|preset| *preset == rule);
+                let next = at.map_or(0, |at| (at + 1) % elementary::PRESETS.len());
+
+                Automaton::Elementary(elementary::PRESETS[next])
+            },
+        }
+    }
+}
+
+/// The name of the rule, or its rule string if it hasn't got one.
+impl fmt::Display for Automaton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Automaton::Generations(rule) => match generations::PRESETS.iter().find(|(_, preset)| preset == rule) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{rule}"),
+            },
+            Automaton::Wireworld => f.write_str("Wireworld"),
+            Automaton::Elementary(rule) => write!(f, "Rule {}", rule.rule),
+        }
+    }
+}
+
+impl Default for Automaton {
+    fn default() -> Self {
+        Automaton::Generations(Generations::default())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{elementary::{RULE_110, RULE_184, RULE_30, RULE_90}, generations::STICKS, grid::Grid, rule};
+
+    #[test]
+    fn steps_with_the_matching_engine() {
+        let mut world = Grid::<5, 5>::new();
+        world[(4, 1)] = 1;
+        world[(4, 2)] = 1;
+        world[(4, 3)] = 1;
+
+        let life = Automaton::Generations(Generations::from_rule(rule::LIFE));
+        let elementary = Automaton::Elementary(RULE_90);
+
+        assert_eq!(
+            life.step(&world, Boundary::Dead, Neighborhood::Moore),
+            update_automata(&world, Boundary::Dead, Neighborhood::Moore, rule::LIFE),
+        );
+        assert_eq!(
+            elementary.step(&world, Boundary::Dead, Neighborhood::Moore),
+            RULE_90.update(&world, Boundary::Dead),
+        );
+    }
+
+    #[test]
+    fn cycles_through_the_presets() {
+        let life = Automaton::Generations(Generations::from_rule(rule::LIFE));
+        let highlife = Automaton::Generations(Generations::from_rule(rule::HIGHLIFE));
+
+        assert_eq!(life.next_rule(), highlife);
+        assert_eq!(Automaton::Generations(STICKS).next_rule(), life);
+        assert_eq!(Automaton::Generations(Generations::new("B36/S125")).next_rule(), life);
+        assert_eq!(Automaton::Elementary(RULE_110).next_rule(), Automaton::Elementary(RULE_184));
+        assert_eq!(Automaton::Elementary(RULE_184).next_rule(), Automaton::Elementary(RULE_30));
+        assert_eq!(Automaton::Wireworld.next_rule(), Automaton::Wireworld);
+    }
+
+    #[test]
+    fn displays_the_rule_name() {
+        assert_eq!(Automaton::Generations(STICKS).to_string(), "Sticks");
+        assert_eq!(Automaton::Generations(Generations::new("B36/S125")).to_string(), "B36/S125");
+        assert_eq!(Automaton::Wireworld.to_string(), "Wireworld");
+        assert_eq!(Automaton::Elementary(RULE_90).to_string(), "Rule 90");
+    }
+}
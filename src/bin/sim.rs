@@ -0,0 +1,309 @@
+//! The firmware's state machine and engine run in a terminal on the host, so
+//!  that rules and modes can be tried out without flashing the board.
+//!
+//! The LEDs are drawn in red at their brightness, beside the whole world with
+//!  the part under the viewport picked out. Keys stand in for the buttons: `a`
+//!  and `b` click them, `A` and `B` hold them down for a long press and space
+//!  presses both together. `q` quits.
+//!
+//! The board's [`Simulation`] runs here as it is, with only its LEDs, RTC0's
+//!  ticks and its flash stood in for, so what happens should match the board,
+//!  apart from button timing.
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use automata::{
+    app::{App, Effects},
+    display::{shade, Screen, DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    elementary,
+    input::{Button, Event},
+    rule, storage, Automaton, Flash, Generations, Grid, Hardware, Simulation, Speed, Storage, World, XorShift32,
+};
+use microbit::display::nonblocking::MAX_BRIGHTNESS;
+
+
+/// The same choices as the firmware's menu.
+const MODES: [Automaton; 3] = [
+    Automaton::Generations(Generations::from_rule(rule::LIFE)),
+    Automaton::Wireworld,
+    Automaton::Elementary(elementary::RULE_30),
+];
+
+type Universe = Grid<32, 32>;
+
+/// The board's flash, kept in a file between runs so that the simulator picks
+///  up where it left off, as the board does. The file is written once a whole
+///  record is, and when quitting.
+struct FileFlash {
+    words: Vec<u32>,
+    path: PathBuf,
+    /// Whether anything has changed since the file was last written.
+    dirty: bool,
+}
+
+impl FileFlash {
+    fn open(path: PathBuf) -> Self {
+        let mut words = vec![storage::ERASED; Storage::<Self>::PAGES * Self::PAGE_SIZE / 4];
+
+        if let Ok(bytes) = fs::read(&path) {
+            for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+                *word = u32::from_le_bytes(bytes.try_into().unwrap());
+            }
+        }
+
+        FileFlash { words, path, dirty: false }
+    }
+}
+
+impl Flash for FileFlash {
+    const PAGE_SIZE: usize = 1024;
+
+    fn read(&self, address: usize) -> u32 {
+        self.words[address / 4]
+    }
+
+    fn erase(&mut self, page: usize) {
+        let words = Self::PAGE_SIZE / 4;
+        self.words[page * words..(page + 1) * words].fill(storage::ERASED);
+        self.dirty = true;
+    }
+
+    /// Flash can only clear bits, which this keeps to.
+    fn write(&mut self, address: usize, word: u32) {
+        self.words[address / 4] &= word;
+        self.dirty = true;
+    }
+
+    fn flush(&mut self) {
+        if self.dirty {
+            let bytes = self.words.iter().flat_map(|word| word.to_le_bytes()).collect::<Vec<_>>();
+            let _ = fs::write(&self.path, bytes);
+            self.dirty = false;
+        }
+    }
+}
+
+impl Drop for FileFlash {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+
+/// The board's LEDs, and RTC0's ticks kept by the clock.
+#[derive(Default)]
+struct Leds {
+    screen: Screen,
+    prescaler: Option<u32>,
+}
+
+impl Leds {
+    /// How long until the next tick, or `None` while RTC0 would be stopped.
+    fn tick_period(&self) -> Option<Duration> {
+        self.prescaler
+            .map(|prescaler| Duration::from_micros((prescaler as u64 + 1) * 1_000_000 / Speed::CLOCK_HZ as u64))
+    }
+}
+
+impl Hardware for Leds {
+    fn show(&mut self, screen: &Screen) {
+        self.screen = *screen;
+    }
+
+    fn set_ticks(&mut self, prescaler: Option<u32>) {
+        self.prescaler = prescaler;
+    }
+}
+
+/// The board as the simulation sees it, with stand-ins for its hardware.
+type Board = Simulation<Universe, FileFlash, Leds>;
+
+
+/// A key pressed, as the button event it stands for.
+enum Key {
+    Button(Event),
+    Quit,
+}
+
+fn key(byte: u8) -> Option<Key> {
+    Some(match byte {
+        b'a' => Key::Button(Event::Release(Button::A)),
+        b'b' => Key::Button(Event::Release(Button::B)),
+        b'A' => Key::Button(Event::LongPress(Button::A)),
+        b'B' => Key::Button(Event::LongPress(Button::B)),
+        b' ' => Key::Button(Event::Chord),
+        // Ctrl-C comes through as a byte, since the terminal's signals are
+        //  off while the simulator runs.
+        b'q' | b'Q' | 3 => Key::Quit,
+        _ => return None,
+    })
+}
+
+
+/// Puts the terminal into a mode where keys arrive as they're pressed, without
+///  being echoed, and puts it back as it was when dropped.
+struct RawTerminal {
+    saved: String,
+}
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "1"])?;
+        print!("\x1b[?25l\x1b[2J");
+
+        Ok(RawTerminal { saved: saved.trim().to_string() })
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+        println!("\x1b[0m\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+
+/// A brightness from 0 to [`MAX_BRIGHTNESS`] as the red of an LED.
+fn led(brightness: u8) -> (u8, u8, u8) {
+    match brightness {
+        0 => (40, 8, 8),
+        level => ((95 + level as u32 * 160 / MAX_BRIGHTNESS as u32) as u8, 0, 0),
+    }
+}
+
+/// Draws the LEDs beside the whole world, two rows of cells to a line, with
+///  what's under the viewport on a lighter background.
+fn render(board: &Board, app: &App, last: Option<Event>) -> String {
+    let mut out = String::from("\x1b[H");
+    let viewport = board.viewport();
+    let settings = board.settings();
+    let stats = board.stats();
+    let in_view = |row: usize, col: usize| {
+        (row + Universe::HEIGHT - viewport.row) % Universe::HEIGHT < DISPLAY_HEIGHT
+            && (col + Universe::WIDTH - viewport.col) % Universe::WIDTH < DISPLAY_WIDTH
+    };
+    let cell = |row: usize, col: usize| match shade(&settings.automaton, board.world().get(row, col)) {
+        0 if in_view(row, col) => (48, 48, 64),
+        0 => (16, 16, 16),
+        level => led(level),
+    };
+
+    for line in 0..Universe::HEIGHT / 2 {
+        out.push_str("  ");
+        match line.checked_sub(1).filter(|row| *row < DISPLAY_HEIGHT) {
+            Some(row) => {
+                for col in 0..DISPLAY_WIDTH {
+                    let (r, g, b) = led(board.hardware().screen[(row, col)]);
+                    out.push_str(&format!("\x1b[38;2;{r};{g};{b}m\u{25cf} "));
+                }
+            },
+            None => out.push_str(&" ".repeat(DISPLAY_WIDTH * 2)),
+        }
+        out.push_str("\x1b[0m    ");
+
+        for col in 0..Universe::WIDTH {
+            let (top, bottom) = (cell(line * 2, col), cell(line * 2 + 1, col));
+            out.push_str(&format!(
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                top.0, top.1, top.2, bottom.0, bottom.1, bottom.2,
+            ));
+        }
+        out.push_str("\x1b[0m\x1b[K\r\n");
+    }
+
+    let last = last.map_or(String::new(), |event| format!("{event:?}"));
+    out.push_str(&format!(
+        "\r\n  {:?}, after {last}\x1b[K\r\n  {}   Edges: {}   Speed: {}\x1b[K\r\n  Generation {}, population {}\x1b[K\r\n",
+        app.mode(),
+        settings.automaton,
+        settings.boundary,
+        settings.speed,
+        stats.generation,
+        stats.population,
+    ));
+    out.push_str("\r\n  a, b: click   A, B: hold   space: both   q: quit\x1b[K\r\n");
+
+    out
+}
+
+
+fn main() -> io::Result<()> {
+    let flash = FileFlash::open(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target").join("sim.flash"));
+    let storage = Storage::new(flash);
+
+    let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u32);
+    let mut board = Board::new(&MODES, storage, Leds::default(), XorShift32::new(seed));
+    let mut app = board.pick_up();
+    app.start(&mut board);
+
+    let _terminal = RawTerminal::enter()?;
+    let (keys, presses) = mpsc::channel();
+    thread::spawn(move || {
+        for byte in io::stdin().lock().bytes() {
+            if byte.map_or(true, |byte| keys.send(byte).is_err()) {
+                break;
+            }
+        }
+    });
+
+    let mut next_tick = Instant::now();
+    let mut last = None;
+
+    loop {
+        if app.mode().is_running() && board.reseed_due() {
+            board.reseed();
+        }
+
+        print!("{}", render(&board, &app, last));
+        io::stdout().flush()?;
+
+        let wait = match board.hardware().tick_period() {
+            Some(_) => next_tick.saturating_duration_since(Instant::now()),
+            None => Duration::from_secs(3600),
+        };
+
+        match presses.recv_timeout(wait) {
+            Ok(byte) => match key(byte) {
+                Some(Key::Button(event)) => {
+                    app.handle(event, &mut board);
+                    last = Some(event);
+                },
+                Some(Key::Quit) => break,
+                None => {},
+            },
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(period) = board.hardware().tick_period() {
+                    board.tick();
+                    next_tick = Instant::now().max(next_tick) + period;
+                }
+            },
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+
+        // RTC0 starts counting again from when it's started.
+        if board.hardware().tick_period().is_none() {
+            next_tick = Instant::now();
+        }
+    }
+
+    Ok(())
+}
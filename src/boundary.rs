@@ -0,0 +1,159 @@
+//! What lies past the edge of a world.
+
+use core::fmt;
+
+/// How neighbours past the edge of a world are found.
+///
+/// The twisted variants glue the edges of the rectangle the way the surfaces
+///  they're named after are built: a Klein bottle flips the columns whenever
+///  the top and bottom edges are crossed, and the projective plane also flips
+///  the rows when crossing the left and right edges.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Boundary {
+    /// Opposite edges are joined, so the world is a doughnut.
+    #[default]
+    Torus,
+    /// Everything past the edge is permanently dead.
+    Dead,
+    /// The edge is a mirror, so a cell's neighbours past it are reflections
+    ///  of the cells just inside it.
+    Mirror,
+    KleinBottle,
+    ProjectivePlane,
+}
+
+impl Boundary {
+    /// The boundary after this one, for cycling through them all.
+    pub const fn next(self) -> Boundary {
+        match self {
+            Boundary::Torus => Boundary::Dead,
+            Boundary::Dead => Boundary::Mirror,
+            Boundary::Mirror => Boundary::KleinBottle,
+            Boundary::KleinBottle => Boundary::ProjectivePlane,
+            Boundary::ProjectivePlane => Boundary::Torus,
+        }
+    }
+
+    /// Where `row`, `col` ends up in a `width` by `height` world, or `None` if
+    ///  it falls off the edge.
+    pub const fn resolve(self, row: isize, col: isize, width: usize, height: usize) -> Option<(usize, usize)> {
+        let (width, height) = (width as isize, height as isize);
+
+        // Nearly every neighbour is inside, and the wrapping below divides,
+        //  which the micro:bit's Cortex-M0 can only do in software.
+        if 0 <= row && row < height && 0 <= col && col < width {
+            return Some((row as usize, col as usize));
+        }
+
+        match self {
+            Boundary::Torus => Some((row.rem_euclid(height) as usize, col.rem_euclid(width) as usize)),
+
+            Boundary::Dead => None,
+
+            Boundary::Mirror => Some((reflect(row, height), reflect(col, width))),
+
+            Boundary::KleinBottle => {
+                let mut wrapped_col = col.rem_euclid(width);
+                if row.div_euclid(height) % 2 != 0 {
+                    wrapped_col = width - 1 - wrapped_col;
+                }
+
+                Some((row.rem_euclid(height) as usize, wrapped_col as usize))
+            },
+
+            Boundary::ProjectivePlane => {
+                let mut wrapped_row = row.rem_euclid(height);
+                let mut wrapped_col = col.rem_euclid(width);
+                if col.div_euclid(width) % 2 != 0 {
+                    wrapped_row = height - 1 - wrapped_row;
+                }
+                if row.div_euclid(height) % 2 != 0 {
+                    wrapped_col = width - 1 - wrapped_col;
+                }
+
+                Some((wrapped_row as usize, wrapped_col as usize))
+            },
+        }
+    }
+}
+
+impl fmt::Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Boundary::Torus => "Torus",
+            Boundary::Dead => "Dead",
+            Boundary::Mirror => "Mirror",
+            Boundary::KleinBottle => "Klein bottle",
+            Boundary::ProjectivePlane => "Projective plane",
+        })
+    }
+}
+
+/// Folds `index` back into `0..length` as if both ends were mirrors, so `-1`
+///  lands on `0` and `length` on `length - 1`.
+const fn reflect(index: isize, length: isize) -> usize {
+    let folded = index.rem_euclid(2 * length);
+
+    if folded < length {
+        folded as usize
+    } else {
+        (2 * length - 1 - folded) as usize
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inside_cells_are_untouched() {
+        for boundary in [Boundary::Torus, Boundary::Dead, Boundary::Mirror, Boundary::KleinBottle, Boundary::ProjectivePlane] {
+            assert_eq!(boundary.resolve(2, 3, 5, 4), Some((2, 3)));
+        }
+    }
+
+    #[test]
+    fn torus_wraps_straight_across() {
+        assert_eq!(Boundary::Torus.resolve(-1, 1, 5, 4), Some((3, 1)));
+        assert_eq!(Boundary::Torus.resolve(1, 5, 5, 4), Some((1, 0)));
+    }
+
+    #[test]
+    fn dead_edge_has_nothing_beyond_it() {
+        assert_eq!(Boundary::Dead.resolve(-1, 1, 5, 4), None);
+        assert_eq!(Boundary::Dead.resolve(1, 5, 5, 4), None);
+    }
+
+    #[test]
+    fn mirror_reflects_the_edge_cells() {
+        assert_eq!(Boundary::Mirror.resolve(-1, 1, 5, 4), Some((0, 1)));
+        assert_eq!(Boundary::Mirror.resolve(1, 5, 5, 4), Some((1, 4)));
+        assert_eq!(Boundary::Mirror.resolve(-2, -1, 5, 4), Some((1, 0)));
+    }
+
+    #[test]
+    fn klein_bottle_flips_across_the_top_and_bottom() {
+        assert_eq!(Boundary::KleinBottle.resolve(-1, 1, 5, 4), Some((3, 3)));
+        assert_eq!(Boundary::KleinBottle.resolve(4, 0, 5, 4), Some((0, 4)));
+        // Left and right are joined without a twist.
+        assert_eq!(Boundary::KleinBottle.resolve(1, 5, 5, 4), Some((1, 0)));
+    }
+
+    #[test]
+    fn projective_plane_flips_across_every_edge() {
+        assert_eq!(Boundary::ProjectivePlane.resolve(-1, 1, 5, 4), Some((3, 3)));
+        assert_eq!(Boundary::ProjectivePlane.resolve(1, 5, 5, 4), Some((2, 0)));
+        assert_eq!(Boundary::ProjectivePlane.resolve(-1, -1, 5, 4), Some((0, 0)));
+    }
+
+    #[test]
+    fn cycles_through_every_boundary() {
+        let boundaries = core::iter::successors(Some(Boundary::Torus), |boundary| Some(boundary.next()))
+            .take(6)
+            .collect::<Vec<_>>();
+
+        assert_eq!(boundaries, [Boundary::Torus, Boundary::Dead, Boundary::Mirror, Boundary::KleinBottle, Boundary::ProjectivePlane, Boundary::Torus]);
+        assert_eq!(Boundary::KleinBottle.to_string(), "Klein bottle");
+    }
+}
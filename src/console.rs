@@ -0,0 +1,340 @@
+//! A line-based console for driving the simulation over the serial port, which
+//!  the micro:bit's interface chip passes through to USB.
+//!
+//! Each line is one command. `load` is the exception: it takes an RLE pattern
+//!  on the lines after it, up to the line that ends the pattern with `!`.
+//!
+//! ```text
+//! rule B36/S23
+//! load
+//! x = 3, y = 3
+//! bob$2bo$3o!
+//! step 4
+//! ```
+
+use crate::{
+    automaton::Automaton,
+    elementary::Elementary,
+    generations::{self, Generations},
+    options::Speed,
+    rule::ParseRuleError,
+};
+
+
+/// The most generations one `step` moves on, so that a typo can't keep the
+///  board busy for minutes.
+pub const MAX_STEPS: u32 = 1000;
+
+
+/// One line from the console, parsed.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum Command<'a> {
+    Help,
+    Pause,
+    Run,
+    /// Pauses, then moves the world on by this many generations, at most
+    ///  [`MAX_STEPS`].
+    Step(u32),
+    Rule(Automaton),
+    /// Replaces the world with the RLE pattern in the text.
+    Load(&'a str),
+    /// Writes the world out as RLE.
+    Dump,
+    /// Changes the speed, or for `None` says what it is.
+    Speed(Option<Speed>),
+    /// Replaces the world with a fresh one, from this seed if one's given.
+    Seed(Option<u32>),
+}
+
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum ParseCommandError {
+    UnknownCommand,
+    MissingArgument,
+    UnexpectedArgument,
+    InvalidNumber,
+    InvalidRule(ParseRuleError),
+    /// A line, or a pattern being loaded, too long for the console's buffer.
+    TooLong,
+    /// A byte that isn't printable ASCII.
+    UnexpectedCharacter(u8),
+}
+
+impl From<ParseRuleError> for ParseCommandError {
+    fn from(error: ParseRuleError) -> Self {
+        ParseCommandError::InvalidRule(error)
+    }
+}
+
+
+/// Every command, with what it takes, for `help`.
+pub const HELP: &str = "\
+pause             stop stepping the world
+run               start stepping it again
+step [N]          pause and step N generations, 1 by default, 1000 at most
+rule RULE         B3/S23, B2/S/3, a preset name, wireworld, or W30
+load              read an RLE pattern from the next lines, up to '!'
+dump              write the world as RLE
+speed [1-5]       set the speed, or say what it is
+seed [N]          start a fresh world, from seed N if given
+help              list the commands";
+
+
+/// Parses one line, ignoring case in the command and any extra whitespace.
+pub fn parse(line: &str) -> Result<Command<'_>, ParseCommandError> {
+    if let Some(byte) = line.bytes().find(|byte| !(byte.is_ascii_graphic() || byte.is_ascii_whitespace())) {
+        return Err(ParseCommandError::UnexpectedCharacter(byte));
+    }
+
+    let line = line.trim_start();
+    let (name, rest) = line.split_once(|c: char| c.is_ascii_whitespace()).unwrap_or((line, ""));
+    let argument = Some(rest.trim()).filter(|argument| !argument.is_empty());
+
+    let is = |command: &str| name.eq_ignore_ascii_case(command);
+    let command = if is("help") || is("?") {
+        Command::Help
+    } else if is("pause") {
+        Command::Pause
+    } else if is("run") {
+        Command::Run
+    } else if is("step") {
+        Command::Step(argument.map_or(Ok(1), steps)?)
+    } else if is("rule") {
+        Command::Rule(rule(argument.ok_or(ParseCommandError::MissingArgument)?)?)
+    } else if is("load") {
+        // The pattern is free-form, so it's passed on as it is.
+        return Ok(Command::Load(rest));
+    } else if is("dump") {
+        Command::Dump
+    } else if is("speed") {
+        Command::Speed(argument.map(speed).transpose()?)
+    } else if is("seed") {
+        Command::Seed(argument.map(number).transpose()?)
+    } else {
+        return Err(ParseCommandError::UnknownCommand);
+    };
+
+    match (command, argument) {
+        (Command::Help | Command::Pause | Command::Run | Command::Dump, Some(_)) => {
+            Err(ParseCommandError::UnexpectedArgument)
+        },
+        _ => Ok(command),
+    }
+}
+
+fn number(argument: &str) -> Result<u32, ParseCommandError> {
+    argument.parse().map_err(|_| ParseCommandError::InvalidNumber)
+}
+
+fn steps(argument: &str) -> Result<u32, ParseCommandError> {
+    Some(number(argument)?).filter(|steps| *steps <= MAX_STEPS).ok_or(ParseCommandError::InvalidNumber)
+}
+
+/// A speed by its place in [`Speed::ALL`], counting from one.
+fn speed(argument: &str) -> Result<Speed, ParseCommandError> {
+    let index = number(argument)? as usize;
+
+    index.checked_sub(1).and_then(|index| Speed::ALL.get(index)).copied().ok_or(ParseCommandError::InvalidNumber)
+}
+
+/// Wireworld, an elementary rule by its Wolfram code as in `W30`, a preset by
+///  name, or a Generations rule.
+fn rule(argument: &str) -> Result<Automaton, ParseCommandError> {
+    if argument.eq_ignore_ascii_case("wireworld") {
+        return Ok(Automaton::Wireworld);
+    }
+
+    if let Some(code) = argument.strip_prefix(['W', 'w']).filter(|code| code.bytes().all(|byte| byte.is_ascii_digit())) {
+        let code = code.parse().map_err(|_| ParseCommandError::InvalidNumber)?;
+        return Ok(Automaton::Elementary(Elementary::new(code)));
+    }
+
+    if let Some((_, preset)) = generations::PRESETS.iter().find(|(name, _)| name.eq_ignore_ascii_case(argument)) {
+        return Ok(Automaton::Generations(*preset));
+    }
+
+    Ok(Automaton::Generations(Generations::parse(argument)?))
+}
+
+
+/// Gathers bytes from the serial port into lines, and lines into commands.
+#[derive(Clone, Debug)]
+pub struct Console<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    /// Where the line being typed starts, after any earlier lines of a pattern.
+    line: usize,
+    /// Whether more came than the buffer could hold, since the last command.
+    overflowed: bool,
+}
+
+impl<const N: usize> Console<N> {
+    pub const fn new() -> Self {
+        Console { buffer: [0; N], len: 0, line: 0, overflowed: false }
+    }
+
+    /// Takes the next byte from the serial port, and returns the command once
+    ///  a line ends one. Blank lines are skipped, so `\r\n` is fine, and
+    ///  backspace takes back the last byte of the line being typed.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Command<'_>, ParseCommandError>> {
+        match byte {
+            b'\r' | b'\n' => self.end_line(),
+            // Backspace and delete, which terminals send for the same key.
+            0x08 | 0x7f => {
+                if self.len > self.line {
+                    self.len -= 1;
+                }
+                None
+            },
+            _ => {
+                match self.buffer.get_mut(self.len) {
+                    Some(slot) => {
+                        *slot = byte;
+                        self.len += 1;
+                    },
+                    None => self.overflowed = true,
+                }
+                None
+            },
+        }
+    }
+
+    /// Whether a pattern is being loaded, and more lines are wanted for it.
+    pub fn is_loading(&self) -> bool {
+        self.line > 0
+    }
+
+    fn end_line(&mut self) -> Option<Result<Command<'_>, ParseCommandError>> {
+        let line = &self.buffer[self.line..self.len];
+
+        if line.iter().all(u8::is_ascii_whitespace) && !self.overflowed {
+            self.len = self.line;
+            return None;
+        }
+
+        // A `load` goes on until a line that isn't a comment has a `!`.
+        let loading = self.is_loading() || starts_with_load(&self.buffer[..self.len]);
+        let finished = !line.starts_with(b"#") && line.contains(&b'!');
+        if loading && !finished && !self.overflowed {
+            if self.len < N {
+                self.buffer[self.len] = b'\n';
+                self.len += 1;
+                self.line = self.len;
+                return None;
+            }
+            self.overflowed = true;
+        }
+
+        let (len, overflowed) = (self.len, self.overflowed);
+        self.len = 0;
+        self.line = 0;
+        self.overflowed = false;
+
+        if overflowed {
+            return Some(Err(ParseCommandError::TooLong));
+        }
+        Some(match core::str::from_utf8(&self.buffer[..len]) {
+            Ok(text) => parse(text),
+            Err(error) => Err(ParseCommandError::UnexpectedCharacter(self.buffer[error.valid_up_to()])),
+        })
+    }
+}
+
+impl<const N: usize> Default for Console<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn starts_with_load(text: &[u8]) -> bool {
+    let text = text.trim_ascii_start();
+
+    text.len() >= 4
+        && text[..4].eq_ignore_ascii_case(b"load")
+        && text.get(4).is_none_or(u8::is_ascii_whitespace)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{elementary::RULE_30, generations::BRIANS_BRAIN, rule};
+
+    fn type_in<const N: usize>(console: &mut Console<N>, text: &str) -> Vec<Result<String, ParseCommandError>> {
+        let mut commands = Vec::new();
+        for byte in text.bytes() {
+            if let Some(command) = console.push(byte) {
+                commands.push(command.map(|command| format!("{command:?}")));
+            }
+        }
+
+        commands
+    }
+
+    #[test]
+    fn parses_simple_commands() {
+        assert_eq!(parse("pause"), Ok(Command::Pause));
+        assert_eq!(parse("  RUN "), Ok(Command::Run));
+        assert_eq!(parse("step"), Ok(Command::Step(1)));
+        assert_eq!(parse("step 10"), Ok(Command::Step(10)));
+        assert_eq!(parse("step 1000"), Ok(Command::Step(MAX_STEPS)));
+        assert_eq!(parse("dump"), Ok(Command::Dump));
+        assert_eq!(parse("speed"), Ok(Command::Speed(None)));
+        assert_eq!(parse("speed 5"), Ok(Command::Speed(Some(Speed::Fastest))));
+        assert_eq!(parse("seed"), Ok(Command::Seed(None)));
+        assert_eq!(parse("seed 1234"), Ok(Command::Seed(Some(1234))));
+        assert_eq!(parse("?"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn parses_rules() {
+        let highlife = Automaton::Generations(Generations::from_rule(rule::HIGHLIFE));
+
+        assert_eq!(parse("rule B36/S23"), Ok(Command::Rule(highlife)));
+        assert_eq!(parse("rule highlife"), Ok(Command::Rule(highlife)));
+        assert_eq!(parse("rule Brian's Brain"), Ok(Command::Rule(Automaton::Generations(BRIANS_BRAIN))));
+        assert_eq!(parse("rule WireWorld"), Ok(Command::Rule(Automaton::Wireworld)));
+        assert_eq!(parse("rule W30"), Ok(Command::Rule(Automaton::Elementary(RULE_30))));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        assert_eq!(parse("jump"), Err(ParseCommandError::UnknownCommand));
+        assert_eq!(parse("rule"), Err(ParseCommandError::MissingArgument));
+        assert_eq!(parse("pause now"), Err(ParseCommandError::UnexpectedArgument));
+        assert_eq!(parse("step -1"), Err(ParseCommandError::InvalidNumber));
+        assert_eq!(parse("step 1001"), Err(ParseCommandError::InvalidNumber));
+        assert_eq!(parse("speed 6"), Err(ParseCommandError::InvalidNumber));
+        assert_eq!(parse("rule W256"), Err(ParseCommandError::InvalidNumber));
+        assert_eq!(parse("rule B9"), Err(ParseCommandError::InvalidRule(ParseRuleError::InvalidCount(9))));
+        assert_eq!(parse("step\u{7}"), Err(ParseCommandError::UnexpectedCharacter(7)));
+    }
+
+    #[test]
+    fn gathers_lines_into_commands() {
+        let mut console = Console::<64>::new();
+
+        assert_eq!(type_in(&mut console, "pause\r\n\r\nstep 3\r"), [Ok("Pause".into()), Ok("Step(3)".into())]);
+        assert_eq!(type_in(&mut console, "sept\x7f\x7f\x7fpeed 2\n"), [Ok("Speed(Some(Slow))".into())]);
+    }
+
+    #[test]
+    fn loads_patterns_over_several_lines() {
+        let mut console = Console::<64>::new();
+
+        assert_eq!(type_in(&mut console, "load\r\n#C Hello!\r\nx = 3, y = 3\r\nbob$2bo$\r\n"), []);
+        assert!(console.is_loading());
+
+        let commands = type_in(&mut console, "3o!\r\ndump\r\n");
+        assert_eq!(commands, [Ok(r##"Load("#C Hello!\nx = 3, y = 3\nbob$2bo$\n3o!")"##.into()), Ok("Dump".into())]);
+        assert!(!console.is_loading());
+    }
+
+    #[test]
+    fn drops_what_overflows() {
+        let mut console = Console::<8>::new();
+
+        assert_eq!(type_in(&mut console, "step 1000000\n"), [Err(ParseCommandError::TooLong)]);
+        assert_eq!(type_in(&mut console, "load\nx = 3, y = 3\n"), [Err(ParseCommandError::TooLong)]);
+        assert_eq!(type_in(&mut console, "run\n"), [Ok("Run".into())]);
+    }
+}
@@ -0,0 +1,193 @@
+//! The one place that knows the world ends up on a 5x5 LED matrix.
+
+use microbit::display::nonblocking::{GreyscaleImage, MAX_BRIGHTNESS};
+use tiny_led_matrix::Render;
+
+use crate::{automaton::Automaton, grid::Grid, wireworld};
+
+
+pub const DISPLAY_WIDTH: usize = 5;
+pub const DISPLAY_HEIGHT: usize = 5;
+
+/// A grid the size of the LED matrix, where every cell is a brightness.
+pub type Screen = Grid<DISPLAY_WIDTH, DISPLAY_HEIGHT>;
+
+
+/// How bright a cell in `state` is under a rule with `states` states: live
+///  cells are at full brightness and dying ones fade out, one step per tick.
+pub const fn fade(state: u8, states: u8) -> u8 {
+    match state {
+        0 => 0,
+        1 => MAX_BRIGHTNESS,
+        _ if state >= states => 0,
+        // Spread the dying states evenly over the dimmer levels, but never
+        //  quite down to off so that the last one is still visible.
+        dying => {
+            let remaining = (states - dying) as u16;
+            let level = remaining * (MAX_BRIGHTNESS as u16 - 1) / (states as u16 - 1) + 1;
+
+            level as u8
+        },
+    }
+}
+
+
+/// Wireworld's four states at clearly different brightnesses, with the wires
+///  dim enough that the electrons stand out.
+pub const fn wireworld_brightness(state: u8) -> u8 {
+    match state {
+        wireworld::HEAD => MAX_BRIGHTNESS,
+        wireworld::TAIL => 4,
+        wireworld::CONDUCTOR => 1,
+        _ => 0,
+    }
+}
+
+/// How bright a cell in `state` is when running `automaton`.
+pub const fn shade(automaton: &Automaton, state: u8) -> u8 {
+    match automaton {
+        Automaton::Generations(rule) => fade(state, rule.states),
+        Automaton::Wireworld => wireworld_brightness(state),
+        Automaton::Elementary(_) if state != 0 => MAX_BRIGHTNESS,
+        Automaton::Elementary(_) => 0,
+    }
+}
+
+/// A picture of what `automaton` looks like, for choosing between them.
+pub const fn icon(automaton: &Automaton) -> Screen {
+    match automaton {
+        // A glider.
+        Automaton::Generations(_) => Screen::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 0, 9, 0, 0],
+            [0, 0, 0, 9, 0],
+            [0, 9, 9, 9, 0],
+            [0, 0, 0, 0, 0],
+        ]),
+        // An electron on a wire.
+        Automaton::Wireworld => Screen::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [1, 4, 9, 1, 1],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ]),
+        // The first generations of rule 90.
+        Automaton::Elementary(_) => Screen::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 0, 9, 0, 0],
+            [0, 9, 0, 9, 0],
+            [9, 0, 0, 0, 9],
+            [0, 0, 0, 0, 0],
+        ]),
+    }
+}
+
+
+/// `value` in binary across the bottom four rows, most significant bit first,
+///  under a dimmer top row giving `label` the same way. Values too big for the
+///  twenty lights left have them all on.
+pub const fn readout(label: u8, value: u32) -> Screen {
+    const VALUE_BITS: u32 = (DISPLAY_WIDTH * (DISPLAY_HEIGHT - 1)) as u32;
+
+    let value = if value >> VALUE_BITS != 0 { (1 << VALUE_BITS) - 1 } else { value };
+
+    let mut rows = [[0; DISPLAY_WIDTH]; DISPLAY_HEIGHT];
+    let mut col = 0;
+    while col < DISPLAY_WIDTH {
+        let bit = DISPLAY_WIDTH - 1 - col;
+        rows[0][col] = ((label >> bit) & 1) * 3;
+        col += 1;
+    }
+
+    let mut i = 0;
+    while i < VALUE_BITS as usize {
+        let bit = VALUE_BITS as usize - 1 - i;
+        rows[1 + i / DISPLAY_WIDTH][i % DISPLAY_WIDTH] = ((value >> bit) & 1) as u8 * MAX_BRIGHTNESS;
+        i += 1;
+    }
+
+    Screen::from_rows(rows)
+}
+
+impl From<Screen> for GreyscaleImage {
+    fn from(screen: Screen) -> Self {
+        GreyscaleImage::new(screen.rows())
+    }
+}
+
+impl From<&GreyscaleImage> for Screen {
+    fn from(image: &GreyscaleImage) -> Self {
+        let mut screen = Screen::new();
+
+        for row in 0..DISPLAY_HEIGHT {
+            for col in 0..DISPLAY_WIDTH {
+                screen[(row, col)] = image.brightness_at(col, row);
+            }
+        }
+
+        screen
+    }
+}
+
+impl Render for Screen {
+    fn brightness_at(&self, x: usize, y: usize) -> u8 {
+        self[(y, x)]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fade_dims_towards_death() {
+        assert_eq!(fade(0, 3), 0);
+        assert_eq!(fade(1, 3), MAX_BRIGHTNESS);
+        assert_eq!(fade(2, 3), 5);
+
+        let levels = (1..10).map(|state| fade(state, 10)).collect::<Vec<_>>();
+        assert_eq!(levels, [9, 8, 7, 6, 5, 4, 3, 2, 1]);
+
+        // Long-lived rules have more states than brightness levels.
+        assert!((2..200).all(|state| (1..MAX_BRIGHTNESS).contains(&fade(state, 200))));
+    }
+
+    #[test]
+    fn wireworld_states_are_distinct() {
+        let levels = [wireworld::EMPTY, wireworld::HEAD, wireworld::TAIL, wireworld::CONDUCTOR]
+            .map(|state| shade(&Automaton::Wireworld, state));
+
+        assert_eq!(levels, [0, 9, 4, 1]);
+    }
+
+    #[test]
+    fn readout_shows_binary() {
+        assert_eq!(readout(5, 0b1_00000_00000_00011).into_rows(), [
+            [0, 0, 3, 0, 3],
+            [0, 0, 0, 0, 9],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 9, 9],
+        ]);
+
+        assert!(readout(0, u32::MAX).iter().skip(DISPLAY_WIDTH).all(|light| *light == MAX_BRIGHTNESS));
+    }
+
+    #[test]
+    fn greyscale_image_round_trip() {
+        let screen = Screen::from_rows([
+            [0, 9, 0, 9, 0],
+            [9, 5, 9, 5, 9],
+            [9, 5, 5, 5, 9],
+            [0, 9, 5, 9, 0],
+            [0, 0, 9, 0, 0],
+        ]);
+
+        let image = GreyscaleImage::from(screen);
+        assert_eq!(image.brightness_at(1, 0), 9);
+        assert_eq!(image.brightness_at(2, 4), 9);
+        assert_eq!(Screen::from(&image), screen);
+    }
+}
@@ -0,0 +1,127 @@
+//! Drawing patterns by hand, one cell at a time.
+
+use crate::{
+    automaton::Automaton,
+    display::{Screen, DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    viewport::Viewport,
+    wireworld,
+    world::World,
+};
+
+
+/// How bright the cursor is, which is halfway so that it shows up on both
+///  live and dead cells.
+pub const CURSOR_BRIGHTNESS: u8 = 5;
+
+
+/// A cursor over the display, picking out the cell under it.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq)]
+pub struct Cursor {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Cursor {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Cursor { row, col }
+    }
+
+    /// Moves on to the next light in reading order, going back to the top
+    ///  left after the bottom right.
+    pub fn advance(&mut self) {
+        self.col += 1;
+        if self.col == DISPLAY_WIDTH {
+            self.col = 0;
+            self.row = (self.row + 1) % DISPLAY_HEIGHT;
+        }
+    }
+
+    /// Changes the cell of `world` under the cursor to the next state worth
+    ///  drawing with when running `automaton`.
+    pub fn toggle<C: World>(&self, world: &mut C, viewport: Viewport, automaton: &Automaton) {
+        let row = (viewport.row + self.row) % C::HEIGHT;
+        let col = (viewport.col + self.col) % C::WIDTH;
+
+        world.set(row, col, drawing_state(automaton, world.get(row, col)));
+    }
+
+    /// Lights up the cursor on `screen`.
+    pub fn mark(&self, screen: &mut Screen) {
+        screen[(self.row, self.col)] = CURSOR_BRIGHTNESS;
+    }
+}
+
+
+/// The state after `state` when drawing by hand. Cells are just switched on
+///  and off, except in Wireworld, where they go from empty to wire, then to an
+///  electron's head and tail.
+pub const fn drawing_state(automaton: &Automaton, state: u8) -> u8 {
+    match automaton {
+        Automaton::Wireworld => match state {
+            wireworld::EMPTY => wireworld::CONDUCTOR,
+            wireworld::CONDUCTOR => wireworld::HEAD,
+            wireworld::HEAD => wireworld::TAIL,
+            _ => wireworld::EMPTY,
+        },
+        _ if state == 0 => 1,
+        _ => 0,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generations::{Generations, BRIANS_BRAIN}, grid::Grid, rule};
+
+    #[test]
+    fn advances_in_reading_order() {
+        let mut cursor = Cursor::new(0, 3);
+        cursor.advance();
+        assert_eq!(cursor, Cursor::new(0, 4));
+        cursor.advance();
+        assert_eq!(cursor, Cursor::new(1, 0));
+
+        let mut last = Cursor::new(4, 4);
+        last.advance();
+        assert_eq!(last, Cursor::default());
+    }
+
+    #[test]
+    fn toggles_the_cell_under_the_viewport() {
+        let life = Automaton::Generations(Generations::from_rule(rule::LIFE));
+        let mut world = Grid::<32, 32>::new();
+        let cursor = Cursor::new(1, 2);
+
+        cursor.toggle(&mut world, Viewport::new(30, 31), &life);
+        assert_eq!(world[(31, 1)], 1);
+
+        cursor.toggle(&mut world, Viewport::new(30, 31), &life);
+        assert_eq!(world[(31, 1)], 0);
+    }
+
+    #[test]
+    fn dying_cells_are_cleared() {
+        let brain = Automaton::Generations(BRIANS_BRAIN);
+
+        assert_eq!(drawing_state(&brain, 2), 0);
+    }
+
+    #[test]
+    fn wireworld_cycles_through_its_states() {
+        let states = core::iter::successors(Some(wireworld::EMPTY), |state| Some(drawing_state(&Automaton::Wireworld, *state)))
+            .take(5)
+            .collect::<Vec<_>>();
+
+        assert_eq!(states, [wireworld::EMPTY, wireworld::CONDUCTOR, wireworld::HEAD, wireworld::TAIL, wireworld::EMPTY]);
+    }
+
+    #[test]
+    fn marks_the_cursor() {
+        let mut screen = Screen::new();
+        Cursor::new(2, 3).mark(&mut screen);
+
+        assert_eq!(screen[(2, 3)], CURSOR_BRIGHTNESS);
+        assert_eq!(screen.iter().filter(|light| **light != 0).count(), 1);
+    }
+}
@@ -0,0 +1,130 @@
+//! Wolfram's elementary cellular automata: one row of cells, where each cell
+//!  only sees itself and the cells either side of it.
+
+use crate::{boundary::Boundary, world::World};
+
+
+pub const RULE_30: Elementary = Elementary::new(30);
+pub const RULE_90: Elementary = Elementary::new(90);
+pub const RULE_110: Elementary = Elementary::new(110);
+pub const RULE_184: Elementary = Elementary::new(184);
+
+/// The rules worth a look: chaos, a fractal, a universal computer and traffic.
+pub const PRESETS: [Elementary; 4] = [RULE_30, RULE_90, RULE_110, RULE_184];
+
+
+/// One of the 256 elementary rules, by its Wolfram code: bit `n` of `rule` is
+///  the next state of a cell whose left neighbour, self and right neighbour
+///  spell out `n` in binary.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub struct Elementary {
+    pub rule: u8,
+}
+
+impl Elementary {
+    pub const fn new(rule: u8) -> Self {
+        Elementary { rule }
+    }
+
+    pub const fn next(&self, left: u8, center_cell: u8, right: u8) -> u8 {
+        let pattern = ((left != 0) as u8) << 2 | ((center_cell != 0) as u8) << 1 | (right != 0) as u8;
+
+        (self.rule >> pattern) & 1
+    }
+
+    /// Treats `world` as a history of generations with the newest at the
+    ///  bottom: every row moves up one, losing the oldest off the top, and the
+    ///  next generation is added along the bottom.
+    pub fn update<C: World>(&self, world: &C, boundary: Boundary) -> C {
+        let mut result = C::blank();
+        let newest = C::HEIGHT - 1;
+
+        for row in 0..newest {
+            for col in 0..C::WIDTH {
+                result.set(row, col, world.get(row + 1, col));
+            }
+        }
+
+        // Only the column is resolved, as if the world were the newest row on
+        //  its own. Twisted boundaries would otherwise flip the rows when
+        //  crossing a side, and take the neighbour from the oldest generation.
+        let neighbour = |col: isize| match boundary.resolve(0, col, C::WIDTH, 1) {
+            Some((_, col)) => world.get(newest, col),
+            None => 0,
+        };
+
+        for col in 0..C::WIDTH {
+            result.set(newest, col, self.next(neighbour(col as isize - 1), world.get(newest, col), neighbour(col as isize + 1)));
+        }
+
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn next_reads_the_rule_as_a_lookup_table() {
+        // 30 is 0b00011110.
+        let patterns = [(0, 0, 0), (0, 0, 1), (0, 1, 0), (0, 1, 1), (1, 0, 0), (1, 0, 1), (1, 1, 0), (1, 1, 1)];
+        let states = patterns.map(|(left, center, right)| RULE_30.next(left, center, right));
+
+        assert_eq!(states, [0, 1, 1, 1, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rule_90_draws_a_sierpinski_triangle() {
+        let mut world = Grid::<9, 5>::new();
+        world[(4, 4)] = 1;
+
+        for _ in 0..4 {
+            world = RULE_90.update(&world, Boundary::Dead);
+        }
+
+        assert_eq!(world.into_rows(), [
+            [0, 0, 0, 0, 1, 0, 0, 0, 0],
+            [0, 0, 0, 1, 0, 1, 0, 0, 0],
+            [0, 0, 1, 0, 0, 0, 1, 0, 0],
+            [0, 1, 0, 1, 0, 1, 0, 1, 0],
+            [1, 0, 0, 0, 0, 0, 0, 0, 1],
+        ]);
+    }
+
+    #[test]
+    fn rows_wrap_on_a_torus() {
+        let mut world = Grid::<5, 2>::new();
+        world[(1, 0)] = 1;
+
+        let torus = RULE_90.update(&world, Boundary::Torus);
+        let dead = RULE_90.update(&world, Boundary::Dead);
+
+        assert_eq!(torus.into_rows(), [[1, 0, 0, 0, 0], [0, 1, 0, 0, 1]]);
+        assert_eq!(dead.into_rows(), [[1, 0, 0, 0, 0], [0, 1, 0, 0, 0]]);
+    }
+
+    #[test]
+    fn twisted_edges_keep_to_the_newest_row() {
+        let mut world = Grid::<5, 5>::new();
+        world[(4, 0)] = 1;
+        // The oldest generation, which a flipped row would land on.
+        world[(0, 4)] = 1;
+
+        let torus = RULE_90.update(&world, Boundary::Torus);
+        assert_eq!(RULE_90.update(&world, Boundary::ProjectivePlane), torus);
+        assert_eq!(RULE_90.update(&world, Boundary::KleinBottle), torus);
+        assert_eq!(torus.rows()[4], [0, 1, 0, 0, 1]);
+    }
+
+    #[test]
+    fn rule_184_moves_traffic_right() {
+        let mut world = Grid::<6, 1>::from_rows([[1, 0, 1, 1, 0, 0]]);
+
+        world = RULE_184.update(&world, Boundary::Torus);
+
+        assert_eq!(world.into_rows(), [[0, 1, 1, 0, 1, 0]]);
+    }
+}
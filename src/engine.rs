@@ -0,0 +1,319 @@
+use crate::{boundary::Boundary, neighborhood::Neighborhood, random::Random, world::World};
+
+
+/// A world where every cell is alive or dead on the flip of a coin.
+pub fn random_automata<C: World, R: Random>(random: &mut R) -> C {
+    let mut result = C::blank();
+
+    let mut bits = 0;
+    for i in 0..C::WIDTH * C::HEIGHT {
+        if i % 32 == 0 {
+            bits = random.next_u32();
+        }
+
+        result.set(i / C::WIDTH, i % C::WIDTH, ((bits >> (i % 32)) & 1) as u8);
+    }
+
+    result
+}
+
+/// Decides the next state of a cell from its current state and neighbours.
+///
+/// Plain functions and closures taking `(center_cell, neighbors)` work, as do
+///  rule types such as [`Rule`](crate::rule::Rule).
+///
+/// `neighbors` holds one state per cell of the neighbourhood the world is
+///  being updated with, so its length depends on that.
+pub trait Transition {
+    fn next(&self, center_cell: u8, neighbors: &[u8]) -> u8;
+}
+
+impl<F> Transition for F
+where
+    F: Fn(u8, &[u8]) -> u8
+{
+    fn next(&self, center_cell: u8, neighbors: &[u8]) -> u8 {
+        self(center_cell, neighbors)
+    }
+}
+
+pub fn update_automata<C, T>(automata: &C, boundary: Boundary, neighborhood: Neighborhood, transition_function: T) -> C
+where
+    C: World,
+    T: Transition
+{
+    let mut result = C::blank();
+    let mut buffer = [0; Neighborhood::MAX_NEIGHBORS];
+
+    for row in 0..C::HEIGHT {
+        for col in 0..C::WIDTH {
+            let neighbors = automata.neighbors(row, col, boundary, neighborhood, &mut buffer);
+            result.set(row, col, transition_function.next(automata.get(row, col), neighbors));
+        }
+    }
+
+    result
+}
+
+pub fn conway_transitions(center_cell: u8, neighbors: &[u8]) -> u8 {
+    let live_neighbor_count = neighbors.iter().filter(|n| **n != 0).count();
+
+    match (center_cell, live_neighbor_count) {
+        (0, 3) => 1,
+        (1, 2..=3) => 1,
+        _ => 0,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, random::XorShift32, world::BitGrid};
+
+    fn step(automata: [[u8; 5]; 5]) -> [[u8; 5]; 5] {
+        update_automata(&Grid::from_rows(automata), Boundary::Torus, Neighborhood::Moore, conway_transitions).into_rows()
+    }
+
+    #[test]
+    fn blinker_oscillates_with_period_two() {
+        let horizontal = [
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 1, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+        let vertical = [
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        assert_eq!(step(horizontal), vertical);
+        assert_eq!(step(vertical), horizontal);
+    }
+
+    #[test]
+    fn block_is_a_still_life() {
+        let block = [
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        assert_eq!(step(block), block);
+    }
+
+    #[test]
+    fn tub_is_a_still_life() {
+        let tub = [
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 1, 0, 1, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        assert_eq!(step(tub), tub);
+    }
+
+    #[test]
+    fn glider_wraps_around_the_torus() {
+        let glider = [
+            [0, 1, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [1, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        // A glider moves one cell diagonally every four generations, so after
+        //  twenty it has crossed the 5x5 torus once in each direction.
+        let mut automata = glider;
+        for _ in 0..20 {
+            automata = step(automata);
+        }
+        assert_eq!(automata, glider);
+
+        // After twelve it is split across all four corners.
+        let mut automata = glider;
+        for _ in 0..12 {
+            automata = step(automata);
+        }
+        assert_eq!(automata, [
+            [1, 0, 0, 1, 1],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 1],
+            [1, 0, 0, 0, 0],
+        ]);
+    }
+
+    #[test]
+    fn lone_cell_dies() {
+        let mut automata = [[0; 5]; 5];
+        automata[2][2] = 1;
+
+        assert_eq!(step(automata), [[0; 5]; 5]);
+    }
+
+    #[test]
+    fn larger_worlds_use_the_same_rules() {
+        let mut automata = Grid::<8, 6>::new();
+        automata[(3, 5)] = 1;
+        automata[(3, 6)] = 1;
+        automata[(3, 7)] = 1;
+
+        let automata = update_automata(&automata, Boundary::Torus, Neighborhood::Moore, conway_transitions);
+
+        assert_eq!(automata.iter().filter(|cell| **cell != 0).count(), 3);
+        assert_eq!((automata[(2, 6)], automata[(3, 6)], automata[(4, 6)]), (1, 1, 1));
+    }
+
+    #[test]
+    fn glider_travels_the_same_on_a_bit_grid() {
+        let mut grid = Grid::<16, 16>::new();
+        let mut bits = BitGrid::<16, 16>::new();
+        for (row, col) in [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)] {
+            grid[(row, col)] = 1;
+            bits.set(row, col, 1);
+        }
+
+        for _ in 0..64 {
+            grid = update_automata(&grid, Boundary::Torus, Neighborhood::Moore, conway_transitions);
+            bits = update_automata(&bits, Boundary::Torus, Neighborhood::Moore, conway_transitions);
+        }
+
+        // Sixteen cells down and to the right lands it back where it began.
+        for row in 0..16 {
+            for col in 0..16 {
+                assert_eq!(bits.get(row, col), grid[(row, col)]);
+            }
+        }
+        assert_eq!(bits.get(2, 0), 1);
+        assert_eq!(bits.get(0, 1), 1);
+    }
+
+    #[test]
+    fn dead_edge_stops_gliders() {
+        let glider = [
+            [0, 1, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [1, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        // Without wrapping a glider runs into the corner and turns into a
+        //  block, where the torus would have carried it back round.
+        let mut automata = Grid::from_rows(glider);
+        for _ in 0..20 {
+            automata = update_automata(&automata, Boundary::Dead, Neighborhood::Moore, conway_transitions);
+        }
+
+        assert_eq!(automata.into_rows(), [
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 1, 1],
+            [0, 0, 0, 1, 1],
+        ]);
+    }
+
+    #[test]
+    fn mirror_edge_keeps_a_domino_alive() {
+        // Against a mirror a domino on the edge sees its own reflection, which
+        //  makes it a 2x2 block.
+        let domino = [
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ];
+
+        let automata = Grid::from_rows(domino);
+
+        assert_eq!(update_automata(&automata, Boundary::Mirror, Neighborhood::Moore, conway_transitions), automata);
+        assert_eq!(update_automata(&automata, Boundary::Dead, Neighborhood::Moore, conway_transitions), Grid::new());
+    }
+
+    #[test]
+    fn transitions_see_the_whole_neighborhood() {
+        let mut automata = Grid::<5, 5>::new();
+        automata[(1, 1)] = 1;
+        automata[(2, 3)] = 1;
+
+        let count = |_, neighbors: &[u8]| neighbors.iter().sum::<u8>() * 10 + neighbors.len() as u8;
+
+        let moore = update_automata(&automata, Boundary::Dead, Neighborhood::Moore, count);
+        let von_neumann = update_automata(&automata, Boundary::Dead, Neighborhood::VonNeumann, count);
+        let hexagonal = update_automata(&automata, Boundary::Dead, Neighborhood::Hexagonal, count);
+
+        assert_eq!(moore[(2, 2)], 28);
+        assert_eq!(von_neumann[(2, 2)], 14);
+        // The top left is still a neighbour on the skewed hexagonal grid, but
+        //  the bottom left isn't.
+        assert_eq!(hexagonal[(2, 2)], 26);
+        assert_eq!(moore[(0, 2)], 18);
+        assert_eq!(hexagonal[(0, 2)], 6);
+    }
+
+    #[test]
+    fn von_neumann_life_grows_a_diamond() {
+        // Under B1/S of the four orthogonal neighbours, a single cell grows
+        //  into a diamond rather than a square.
+        let grow = |center_cell, neighbors: &[u8]| {
+            (center_cell == 0 && neighbors.iter().filter(|n| **n != 0).count() == 1) as u8
+        };
+
+        let mut automata = Grid::<5, 5>::new();
+        automata[(2, 2)] = 1;
+
+        let automata = update_automata(&automata, Boundary::Dead, Neighborhood::VonNeumann, grow);
+        assert_eq!(automata.into_rows(), [
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 1, 0, 1, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+    }
+
+    #[test]
+    fn random_automata_only_yields_binary_cells() {
+        let mut random = XorShift32::default();
+
+        for _ in 0..16 {
+            assert!(random_automata::<Grid<5, 5>, _>(&mut random).iter().all(|cell| *cell <= 1));
+        }
+        assert!(random_automata::<Grid<32, 32>, _>(&mut random).iter().all(|cell| *cell <= 1));
+    }
+
+    #[test]
+    fn random_automata_is_reproducible_from_a_seed() {
+        let first: Grid<32, 32> = random_automata(&mut XorShift32::new(7));
+        let again: Grid<32, 32> = random_automata(&mut XorShift32::new(7));
+        let other: Grid<32, 32> = random_automata(&mut XorShift32::new(8));
+
+        assert_eq!(first, again);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn random_automata_doesnt_repeat_itself() {
+        // The middle-square generator this replaced fell into a short cycle
+        //  within a few dozen soups.
+        let mut random = XorShift32::default();
+        let soups = (0..200).map(|_| random_automata::<Grid<5, 5>, _>(&mut random)).collect::<Vec<_>>();
+
+        for (i, soup) in soups.iter().enumerate() {
+            assert!(!soups[..i].contains(soup));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! A font for the LED matrix, five lights high and up to five wide.
+
+/// The lights of one character, column by column from the left, with bit `n`
+///  of a column lighting row `n`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Glyph {
+    pub width: usize,
+    pub columns: [u8; 5],
+}
+
+impl Glyph {
+    /// A glyph from a picture of it, drawn with `#` for a light and `.` for
+    ///  none. Every row must be as wide as the glyph.
+    pub const fn draw(rows: [&str; 5]) -> Glyph {
+        let width = rows[0].len();
+        let mut columns = [0; 5];
+
+        let mut row = 0;
+        while row < 5 {
+            let pixels = rows[row].as_bytes();
+            let mut col = 0;
+            while col < width {
+                if pixels[col] == b'#' {
+                    columns[col] |= 1 << row;
+                }
+                col += 1;
+            }
+            row += 1;
+        }
+
+        Glyph { width, columns }
+    }
+
+    /// Whether the light at `row`, `col` of the glyph is on.
+    pub const fn lit(&self, row: usize, col: usize) -> bool {
+        col < self.width && (self.columns[col] >> row) & 1 != 0
+    }
+}
+
+/// How `c` is drawn. Letters are all drawn in capitals, and anything the font
+///  doesn't have comes out as a question mark.
+pub const fn glyph(c: char) -> Glyph {
+    match c.to_ascii_uppercase() {
+        '0' => Glyph::draw([".##.", "#..#", "#..#", "#..#", ".##."]),
+        '1' => Glyph::draw([".#.", "##.", ".#.", ".#.", "###"]),
+        '2' => Glyph::draw(["###.", "...#", ".##.", "#...", "####"]),
+        '3' => Glyph::draw(["###.", "...#", ".##.", "...#", "###."]),
+        '4' => Glyph::draw(["..##", ".#.#", "#..#", "####", "...#"]),
+        '5' => Glyph::draw(["####", "#...", "###.", "...#", "###."]),
+        '6' => Glyph::draw([".##.", "#...", "###.", "#..#", ".##."]),
+        '7' => Glyph::draw(["####", "...#", "..#.", ".#..", ".#.."]),
+        '8' => Glyph::draw([".##.", "#..#", ".##.", "#..#", ".##."]),
+        '9' => Glyph::draw([".##.", "#..#", ".###", "...#", ".##."]),
+        'A' => Glyph::draw([".##.", "#..#", "####", "#..#", "#..#"]),
+        'B' => Glyph::draw(["###.", "#..#", "###.", "#..#", "###."]),
+        'C' => Glyph::draw([".###", "#...", "#...", "#...", ".###"]),
+        'D' => Glyph::draw(["###.", "#..#", "#..#", "#..#", "###."]),
+        'E' => Glyph::draw(["####", "#...", "###.", "#...", "####"]),
+        'F' => Glyph::draw(["####", "#...", "###.", "#...", "#..."]),
+        'G' => Glyph::draw([".###", "#...", "#.##", "#..#", ".###"]),
+        'H' => Glyph::draw(["#..#", "#..#", "####", "#..#", "#..#"]),
+        'I' => Glyph::draw(["###", ".#.", ".#.", ".#.", "###"]),
+        'J' => Glyph::draw(["..##", "...#", "...#", "#..#", ".##."]),
+        'K' => Glyph::draw(["#..#", "#.#.", "##..", "#.#.", "#..#"]),
+        'L' => Glyph::draw(["#...", "#...", "#...", "#...", "####"]),
+        'M' => Glyph::draw(["#...#", "##.##", "#.#.#", "#...#", "#...#"]),
+        'N' => Glyph::draw(["#...#", "##..#", "#.#.#", "#..##", "#...#"]),
+        'O' => Glyph::draw([".##.", "#..#", "#..#", "#..#", ".##."]),
+        'P' => Glyph::draw(["###.", "#..#", "###.", "#...", "#..."]),
+        'Q' => Glyph::draw([".##..", "#..#.", "#..#.", "#..#.", ".##.#"]),
+        'R' => Glyph::draw(["###.", "#..#", "###.", "#.#.", "#..#"]),
+        'S' => Glyph::draw([".###", "#...", ".##.", "...#", "###."]),
+        'T' => Glyph::draw(["#####", "..#..", "..#..", "..#..", "..#.."]),
+        'U' => Glyph::draw(["#..#", "#..#", "#..#", "#..#", ".##."]),
+        'V' => Glyph::draw(["#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+        'W' => Glyph::draw(["#...#", "#...#", "#.#.#", "##.##", "#...#"]),
+        'X' => Glyph::draw(["#...#", ".#.#.", "..#..", ".#.#.", "#...#"]),
+        'Y' => Glyph::draw(["#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        'Z' => Glyph::draw(["####", "..#.", ".#..", "#...", "####"]),
+        ' ' => Glyph::draw(["..", "..", "..", "..", ".."]),
+        '.' => Glyph::draw([".", ".", ".", ".", "#"]),
+        ',' => Glyph::draw(["..", "..", "..", ".#", "#."]),
+        ':' => Glyph::draw([".", "#", ".", "#", "."]),
+        '!' => Glyph::draw(["#", "#", "#", ".", "#"]),
+        '?' => Glyph::draw([".##.", "#..#", "..#.", "....", "..#."]),
+        '-' => Glyph::draw(["...", "...", "###", "...", "..."]),
+        '/' => Glyph::draw(["...#", "..#.", ".#..", "#...", "...."]),
+        '&' => Glyph::draw([".#..", "#.#.", ".#..", "#.#.", ".#.#"]),
+        '\'' => Glyph::draw(["#", "#", ".", ".", "."]),
+        _ => glyph('?'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_are_drawn_column_by_column() {
+        let one = glyph('1');
+
+        assert_eq!(one.width, 3);
+        assert_eq!(one.columns, [0b10010, 0b11111, 0b10000, 0, 0]);
+        assert!(one.lit(4, 2));
+        assert!(!one.lit(0, 2));
+        assert!(!one.lit(0, 3));
+    }
+
+    #[test]
+    fn letters_ignore_case() {
+        assert_eq!(glyph('b'), glyph('B'));
+    }
+
+    #[test]
+    fn unknown_characters_are_question_marks() {
+        assert_eq!(glyph('~'), glyph('?'));
+        assert_eq!(glyph('é'), glyph('?'));
+    }
+
+    #[test]
+    fn every_glyph_fits_the_display() {
+        let known = ('0'..='9').chain('A'..='Z').chain(" .,:!?-/&'".chars());
+
+        for c in known {
+            assert!((1..=5).contains(&glyph(c).width), "{c}");
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Generations rules, where dying cells take a few ticks to fade away.
+
+use core::{fmt, str::FromStr};
+
+use crate::{
+    engine::Transition,
+    rule::{self, ParseRuleError, Rule},
+};
+
+
+pub const BRIANS_BRAIN: Generations = Generations::new("/2/3");
+pub const STAR_WARS: Generations = Generations::new("345/2/4");
+pub const FROGS: Generations = Generations::new("12/34/3");
+pub const STICKS: Generations = Generations::new("3456/2/6");
+
+/// The well known rules, by name. Life-like rules are Generations rules with
+///  two states, so these include those from [`rule::PRESETS`].
+pub const PRESETS: [(&str, Generations); 11] = [
+    ("Life", Generations::from_rule(rule::LIFE)),
+    ("HighLife", Generations::from_rule(rule::HIGHLIFE)),
+    ("Seeds", Generations::from_rule(rule::SEEDS)),
+    ("Day & Night", Generations::from_rule(rule::DAY_AND_NIGHT)),
+    ("Life without Death", Generations::from_rule(rule::LIFE_WITHOUT_DEATH)),
+    ("Replicator", Generations::from_rule(rule::REPLICATOR)),
+    ("Maze", Generations::from_rule(rule::MAZE)),
+    ("Brian's Brain", BRIANS_BRAIN),
+    ("Star Wars", STAR_WARS),
+    ("Frogs", FROGS),
+    ("Sticks", STICKS),
+];
+
+
+/// A Life-like rule with `states - 2` extra dying states.
+///
+/// State `0` is dead and `1` alive, as for Life. A live cell that doesn't
+///  survive goes to state `2` and counts up by one every tick until it reaches
+///  `states` and dies. Only cells in state `1` count as live neighbours, and
+///  dying cells can't be born again until they're fully dead.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub struct Generations {
+    pub rule: Rule,
+    pub states: u8,
+}
+
+impl Generations {
+    /// Parses a rule at compile time, so it can be baked into flash.
+    ///
+    /// # Panics
+    /// If `rule` isn't a valid rule string, which fails the build when used in
+    ///  a `const`.
+    pub const fn new(rule: &str) -> Generations {
+        match Generations::parse(rule) {
+            Ok(rule) => rule,
+            Err(_) => panic!("invalid rule string"),
+        }
+    }
+
+    /// The two-state rule that behaves exactly like `rule`.
+    pub const fn from_rule(rule: Rule) -> Generations {
+        Generations { rule, states: 2 }
+    }
+
+    /// Parses `B2/S/C3` style rules and the legacy `survival/birth/states`
+    ///  form, such as `/2/3`. Anything [`Rule::parse`] accepts is taken to be
+    ///  a two-state rule.
+    pub const fn parse(rule: &str) -> Result<Generations, ParseRuleError> {
+        let bytes = rule.as_bytes();
+
+        let mut slashes = 0;
+        let mut last_slash = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'/' {
+                slashes += 1;
+                last_slash = i;
+            }
+            i += 1;
+        }
+
+        let (life_like, states) = bytes.split_at(last_slash);
+        let has_states = match states {
+            [b'/', b'C' | b'c', ..] => true,
+            _ => slashes == 2,
+        };
+
+        if !has_states {
+            return match Rule::parse_bytes(bytes) {
+                Ok(rule) => Ok(Generations::from_rule(rule)),
+                Err(error) => Err(error),
+            };
+        }
+
+        let rule = match Rule::parse_bytes(life_like) {
+            Ok(rule) => rule,
+            Err(error) => return Err(error),
+        };
+
+        let mut count: u16 = 0;
+        let mut i = match states {
+            [b'/', b'C' | b'c', ..] => 2,
+            _ => 1,
+        };
+        while i < states.len() {
+            match states[i] {
+                digit @ b'0'..=b'9' => count = count * 10 + (digit - b'0') as u16,
+                byte => return Err(ParseRuleError::UnexpectedCharacter(byte)),
+            }
+            if count > u8::MAX as u16 {
+                return Err(ParseRuleError::InvalidStates);
+            }
+            i += 1;
+        }
+
+        if count < 2 {
+            return Err(ParseRuleError::InvalidStates);
+        }
+
+        Ok(Generations { rule, states: count as u8 })
+    }
+}
+
+impl Default for Generations {
+    fn default() -> Self {
+        Generations::from_rule(Rule::default())
+    }
+}
+
+impl From<Rule> for Generations {
+    fn from(rule: Rule) -> Self {
+        Generations::from_rule(rule)
+    }
+}
+
+impl Transition for Generations {
+    fn next(&self, center_cell: u8, neighbors: &[u8]) -> u8 {
+        let live_neighbor_count = neighbors.iter().filter(|n| **n == 1).count();
+
+        match center_cell {
+            0 if self.rule.is_born(live_neighbor_count) => 1,
+            0 => 0,
+            1 if self.rule.survives(live_neighbor_count) => 1,
+            dying if dying + 1 >= self.states => 0,
+            dying => dying + 1,
+        }
+    }
+}
+
+impl FromStr for Generations {
+    type Err = ParseRuleError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        Generations::parse(rule)
+    }
+}
+
+/// Writes the rule in `B2/S/C3` notation, leaving off the states for two-state
+///  rules.
+impl fmt::Display for Generations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rule)?;
+
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_generations_notation() {
+        assert_eq!(Generations::parse("B2/S/C3"), Ok(BRIANS_BRAIN));
+        assert_eq!(Generations::parse("b2/s/c3"), Ok(BRIANS_BRAIN));
+        assert_eq!(Generations::parse("B2/S345/C4"), Ok(STAR_WARS));
+        assert_eq!(STAR_WARS, Generations { rule: Rule::new("B2/S345"), states: 4 });
+    }
+
+    #[test]
+    fn life_like_rules_have_two_states() {
+        assert_eq!(Generations::parse("B3/S23"), Ok(Generations::from_rule(rule::LIFE)));
+        assert_eq!(Generations::parse("23/3"), Ok(Generations::from_rule(rule::LIFE)));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert_eq!(Generations::parse("B2/S/C1"), Err(ParseRuleError::InvalidStates));
+        assert_eq!(Generations::parse("/2/"), Err(ParseRuleError::InvalidStates));
+        assert_eq!(Generations::parse("/2/256"), Err(ParseRuleError::InvalidStates));
+        assert_eq!(Generations::parse("/2/3x"), Err(ParseRuleError::UnexpectedCharacter(b'x')));
+        assert_eq!(Generations::parse("B9/S/C3"), Err(ParseRuleError::InvalidCount(9)));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        assert_eq!(BRIANS_BRAIN.to_string(), "B2/S/C3");
+        assert_eq!(PRESETS[0].1.to_string(), "B3/S23");
+
+        for (_, rule) in PRESETS {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn dying_cells_fade_through_every_state() {
+        let lonely = [0; 8];
+
+        assert_eq!(STAR_WARS.next(1, &lonely), 2);
+        assert_eq!(STAR_WARS.next(2, &lonely), 3);
+        assert_eq!(STAR_WARS.next(3, &lonely), 0);
+    }
+
+    #[test]
+    fn only_live_cells_count_as_neighbours() {
+        // Two live neighbours give birth under Brian's Brain, but dying ones
+        //  don't count.
+        assert_eq!(BRIANS_BRAIN.next(0, &[1, 1, 0, 0, 0, 0, 0, 0]), 1);
+        assert_eq!(BRIANS_BRAIN.next(0, &[1, 2, 2, 0, 0, 0, 0, 0]), 0);
+        // Nor can a dying cell be born again.
+        assert_eq!(BRIANS_BRAIN.next(2, &[1, 1, 0, 0, 0, 0, 0, 0]), 0);
+    }
+
+    #[test]
+    fn two_state_rules_match_life_like_rules() {
+        let life = Generations::from_rule(rule::LIFE);
+
+        for count in 0..=8 {
+            let mut neighbors = [0; 8];
+            neighbors[..count].fill(1);
+
+            assert_eq!(life.next(0, &neighbors), rule::LIFE.next(0, &neighbors));
+            assert_eq!(life.next(1, &neighbors), rule::LIFE.next(1, &neighbors));
+        }
+    }
+}
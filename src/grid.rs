@@ -0,0 +1,132 @@
+use core::ops::{Index, IndexMut};
+
+use crate::world::World;
+
+
+/// A `W` by `H` world of cells, stored row by row.
+///
+/// Cells are plain `u8` states; the engine treats `0` as dead and the meaning
+///  of anything else is up to the transition function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<const W: usize, const H: usize> {
+    cells: [[u8; W]; H],
+}
+
+impl<const W: usize, const H: usize> Grid<W, H> {
+    /// A grid with every cell dead.
+    pub const fn new() -> Self {
+        Grid { cells: [[0; W]; H] }
+    }
+
+    pub const fn from_rows(cells: [[u8; W]; H]) -> Self {
+        Grid { cells }
+    }
+
+    pub const fn rows(&self) -> &[[u8; W]; H] {
+        &self.cells
+    }
+
+    pub const fn into_rows(self) -> [[u8; W]; H] {
+        self.cells
+    }
+
+    /// Every cell in reading order.
+    pub fn iter(&self) -> impl Iterator<Item = &u8> {
+        self.cells.iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut u8> {
+        self.cells.iter_mut().flatten()
+    }
+
+    /// A copy of the grid with `f` applied to every cell.
+    pub fn map<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u8) -> u8
+    {
+        for cell in self.iter_mut() {
+            *cell = f(*cell);
+        }
+
+        self
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Grid<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> From<[[u8; W]; H]> for Grid<W, H> {
+    fn from(cells: [[u8; W]; H]) -> Self {
+        Self::from_rows(cells)
+    }
+}
+
+impl<const W: usize, const H: usize> World for Grid<W, H> {
+    const WIDTH: usize = W;
+    const HEIGHT: usize = H;
+
+    fn blank() -> Self {
+        Self::new()
+    }
+
+    fn get(&self, row: usize, col: usize) -> u8 {
+        self.cells[row][col]
+    }
+
+    fn set(&mut self, row: usize, col: usize, state: u8) {
+        self.cells[row][col] = state;
+    }
+}
+
+/// Indexed by `(row, col)`.
+impl<const W: usize, const H: usize> Index<(usize, usize)> for Grid<W, H> {
+    type Output = u8;
+
+    fn index(&self, (row, col): (usize, usize)) -> &u8 {
+        &self.cells[row][col]
+    }
+}
+
+impl<const W: usize, const H: usize> IndexMut<(usize, usize)> for Grid<W, H> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut u8 {
+        &mut self.cells[row][col]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{boundary::Boundary, neighborhood::Neighborhood};
+
+    #[test]
+    fn neighbors_wrap_around_the_edges() {
+        let mut grid = Grid::<4, 3>::new();
+        grid[(2, 3)] = 1;   // Bottom right corner
+        grid[(0, 1)] = 2;
+        grid[(1, 0)] = 3;
+
+        let mut buffer = [0; Neighborhood::MAX_NEIGHBORS];
+
+        assert_eq!(grid.neighbors(0, 0, Boundary::Torus, Neighborhood::Moore, &mut buffer), [1, 0, 0, 0, 2, 0, 3, 0]);
+    }
+
+    #[test]
+    fn indexing_is_row_major() {
+        let grid = Grid::from_rows([[0, 1, 2], [3, 4, 5]]);
+
+        assert_eq!(grid[(1, 0)], 3);
+        assert_eq!(grid[(0, 2)], 2);
+        assert_eq!(grid.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn map_applies_to_every_cell() {
+        let grid = Grid::from_rows([[0, 1], [1, 0]]).map(|cell| cell * 7);
+
+        assert_eq!(grid.into_rows(), [[0, 7], [7, 0]]);
+    }
+}
@@ -0,0 +1,255 @@
+//! Spotting worlds that have died out or settled into repeating themselves.
+
+use core::fmt;
+
+use crate::world::World;
+
+
+/// What became of a world that has stopped doing anything new.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub enum Fate {
+    /// Every cell is dead.
+    Extinct,
+    /// Nothing changes from one generation to the next.
+    StillLife,
+    /// The same generations come round again every `_` ticks.
+    Oscillator(usize),
+}
+
+impl Fate {
+    /// How many ticks it takes for the world to come back round.
+    pub const fn period(self) -> usize {
+        match self {
+            Fate::Extinct | Fate::StillLife => 1,
+            Fate::Oscillator(period) => period,
+        }
+    }
+}
+
+
+/// How many generations a world that has died out or settled into repeating
+///  itself stays on show before a fresh soup replaces it, if one ever does.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Reseed {
+    /// Settled worlds are left be.
+    Never,
+    After10,
+    #[default]
+    After30,
+    After100,
+}
+
+impl Reseed {
+    pub const ALL: [Reseed; 4] = [Reseed::Never, Reseed::After10, Reseed::After30, Reseed::After100];
+
+    /// Where this is in [`Reseed::ALL`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The wait after this one, going from never to the longest and round
+    ///  again.
+    pub const fn next(self) -> Reseed {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// How many generations a settled world stays, or `None` for good.
+    pub const fn generations(self) -> Option<u32> {
+        match self {
+            Reseed::Never => None,
+            Reseed::After10 => Some(10),
+            Reseed::After30 => Some(30),
+            Reseed::After100 => Some(100),
+        }
+    }
+
+    /// Whether a world that settled `generations` ago is due to be replaced.
+    pub const fn is_due(self, generations: u32) -> bool {
+        match self.generations() {
+            Some(wait) => generations >= wait,
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for Reseed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.generations() {
+            Some(generations) => write!(f, "After {generations}"),
+            None => f.write_str("Never"),
+        }
+    }
+}
+
+
+/// A fingerprint of every cell of `world`, using FNV-1a. Worlds with the same
+///  cells always get the same fingerprint, and different ones almost never do.
+pub fn fingerprint<C: World>(world: &C) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    for row in 0..C::HEIGHT {
+        for col in 0..C::WIDTH {
+            hash ^= world.get(row, col) as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    hash
+}
+
+
+/// The fingerprints of the last `N` generations, which is enough to recognise
+///  oscillators with a period of up to `N`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct History<const N: usize> {
+    fingerprints: [u64; N],
+    len: usize,
+    /// Where the next fingerprint goes, overwriting the oldest once full.
+    next: usize,
+}
+
+impl<const N: usize> History<N> {
+    pub const fn new() -> Self {
+        History { fingerprints: [0; N], len: 0, next: 0 }
+    }
+
+    /// Forgets every generation, as when a new world is started.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// Adds `world` as the newest generation, and works out its fate if it's
+    ///  a repeat of one of the generations before it.
+    pub fn record<C: World>(&mut self, world: &C) -> Option<Fate> {
+        let fingerprint = fingerprint(world);
+
+        let period = (1..=self.len).find(|ago| self.fingerprints[(self.next + N - ago) % N] == fingerprint);
+
+        if N > 0 {
+            self.fingerprints[self.next] = fingerprint;
+            self.next = (self.next + 1) % N;
+            self.len = (self.len + 1).min(N);
+        }
+
+        let extinct = (0..C::HEIGHT).all(|row| (0..C::WIDTH).all(|col| world.get(row, col) == 0));
+
+        match period? {
+            _ if extinct => Some(Fate::Extinct),
+            1 => Some(Fate::StillLife),
+            period => Some(Fate::Oscillator(period)),
+        }
+    }
+}
+
+impl<const N: usize> Default for History<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{boundary::Boundary, engine::update_automata, grid::Grid, neighborhood::Neighborhood, rule};
+
+    /// Steps `world` under Life until its fate is known, giving up after
+    ///  `limit` generations.
+    fn fate<const W: usize, const H: usize>(mut world: Grid<W, H>, limit: usize) -> Option<Fate> {
+        let mut history = History::<16>::new();
+
+        for _ in 0..limit {
+            if let Some(fate) = history.record(&world) {
+                return Some(fate);
+            }
+            world = update_automata(&world, Boundary::Dead, Neighborhood::Moore, rule::LIFE);
+        }
+
+        None
+    }
+
+    #[test]
+    fn recognises_extinction() {
+        let lonely = Grid::<5, 5>::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 1, 0, 0, 0],
+            [0, 0, 0, 1, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+
+        assert_eq!(fate(lonely, 10), Some(Fate::Extinct));
+    }
+
+    #[test]
+    fn recognises_still_lifes() {
+        let block = Grid::<5, 5>::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 1, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+
+        assert_eq!(fate(block, 10), Some(Fate::StillLife));
+    }
+
+    #[test]
+    fn recognises_oscillators_and_their_period() {
+        let blinker = Grid::<5, 5>::from_rows([
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+
+        let fate = fate(blinker, 10);
+        assert_eq!(fate, Some(Fate::Oscillator(2)));
+        assert_eq!(fate.map(Fate::period), Some(2));
+    }
+
+    #[test]
+    fn periods_longer_than_the_history_go_unnoticed() {
+        let mut history = History::<3>::new();
+        let worlds = (0..4).map(|i| {
+            let mut world = Grid::<4, 1>::new();
+            world[(0, i)] = 1;
+            world
+        }).collect::<Vec<_>>();
+
+        for world in worlds.iter().cycle().take(12) {
+            assert_eq!(history.record(world), None);
+        }
+
+        history.clear();
+        assert_eq!(history.record(&worlds[0]), None);
+    }
+
+    #[test]
+    fn reseeding_can_be_turned_off() {
+        assert!(!Reseed::Never.is_due(u32::MAX));
+        assert!(!Reseed::After30.is_due(29));
+        assert!(Reseed::After30.is_due(30));
+        assert_eq!(Reseed::default().generations(), Some(30));
+        assert_eq!(Reseed::After100.next(), Reseed::Never);
+        assert_eq!(Reseed::Never.to_string(), "Never");
+        assert_eq!(Reseed::After10.to_string(), "After 10");
+    }
+
+    #[test]
+    fn fingerprints_tell_worlds_apart() {
+        let mut world = Grid::<32, 32>::new();
+        let blank = fingerprint(&world);
+        world[(31, 31)] = 1;
+
+        let mut again = Grid::<32, 32>::new();
+        again[(31, 31)] = 1;
+
+        assert_ne!(fingerprint(&world), blank);
+        assert_eq!(fingerprint(&world), fingerprint(&again));
+    }
+}
@@ -0,0 +1,293 @@
+//! Turning the levels of the two buttons into presses, clicks and chords.
+
+/// One of the buttons on the front of the board.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub enum Button {
+    A,
+    B,
+}
+
+
+/// Something the buttons did.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The button went down. Whatever it turns into comes later.
+    Press(Button),
+    /// The button came back up after a short press of its own, which makes it
+    ///  a click. Presses that became long presses or chords end quietly.
+    Release(Button),
+    /// The button has been held down for [`Buttons::LONG_PRESS`] on its own.
+    LongPress(Button),
+    /// A second click of the button within [`Buttons::DOUBLE_CLICK`] of the
+    ///  first, which comes right after that click's `Release`.
+    DoubleClick(Button),
+    /// Both buttons are down together.
+    Chord,
+}
+
+
+/// What a button that's down is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Hold {
+    Up,
+    /// Down on its own, so far.
+    Short,
+    Long,
+    Chord,
+}
+
+/// One button's state, with its level only taken once it's held steady for
+///  [`Buttons::DEBOUNCE`].
+#[derive(Clone, Copy, Debug)]
+struct Debounced {
+    /// The level as last sampled, bounces and all.
+    raw: bool,
+    raw_since: u32,
+    down: bool,
+    down_since: u32,
+    hold: Hold,
+    last_click: Option<u32>,
+}
+
+impl Debounced {
+    const fn new() -> Self {
+        Debounced { raw: false, raw_since: 0, down: false, down_since: 0, hold: Hold::Up, last_click: None }
+    }
+
+    /// Takes in a sample of the button, returning whether its steady level
+    ///  changed.
+    fn sample(&mut self, now: u32, level: bool) -> bool {
+        if level != self.raw {
+            self.raw = level;
+            self.raw_since = now;
+        }
+
+        if self.raw != self.down && now.wrapping_sub(self.raw_since) >= Buttons::DEBOUNCE {
+            self.down = self.raw;
+            self.down_since = now;
+            return true;
+        }
+
+        false
+    }
+}
+
+
+/// Events from both buttons, worked out from samples of whether they're down.
+///
+/// Sample as often as possible and then take the events, which queue up until
+///  they're taken:
+///
+/// ```
+/// # use automata::input::{Button, Buttons, Event};
+/// let mut buttons = Buttons::new();
+/// buttons.sample(0, true, false);
+/// buttons.sample(Buttons::DEBOUNCE, true, false);
+/// assert_eq!(buttons.next_event(), Some(Event::Press(Button::A)));
+/// assert_eq!(buttons.next_event(), None);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Buttons {
+    a: Debounced,
+    b: Debounced,
+    queue: [Event; Self::QUEUE_LEN],
+    /// Where the oldest event waiting is.
+    head: usize,
+    len: usize,
+}
+
+impl Buttons {
+    /// How many milliseconds a button must stay at a level before it counts.
+    pub const DEBOUNCE: u32 = 20;
+    pub const LONG_PRESS: u32 = 600;
+    /// The longest gap between two clicks of a double click, in milliseconds.
+    pub const DOUBLE_CLICK: u32 = 300;
+
+    /// How many events can be waiting at once. Any past that are dropped.
+    const QUEUE_LEN: usize = 8;
+
+    pub const fn new() -> Self {
+        Buttons {
+            a: Debounced::new(),
+            b: Debounced::new(),
+            queue: [Event::Chord; Self::QUEUE_LEN],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Takes in whether each button is down at `now`, in milliseconds from any
+    ///  starting point, and queues up whatever events that makes.
+    pub fn sample(&mut self, now: u32, a: bool, b: bool) {
+        self.sample_one(now, Button::A, a);
+        self.sample_one(now, Button::B, b);
+    }
+
+    /// The oldest event that hasn't been taken yet.
+    pub fn next_event(&mut self) -> Option<Event> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.queue[self.head];
+        self.head = (self.head + 1) % Self::QUEUE_LEN;
+        self.len -= 1;
+
+        Some(event)
+    }
+
+    fn sample_one(&mut self, now: u32, button: Button, level: bool) {
+        let (this, other) = match button {
+            Button::A => (&mut self.a, &mut self.b),
+            Button::B => (&mut self.b, &mut self.a),
+        };
+
+        let mut events = [None; 3];
+
+        if this.sample(now, level) {
+            if this.down {
+                events[0] = Some(Event::Press(button));
+
+                if other.down {
+                    events[1] = Some(Event::Chord);
+                    this.hold = Hold::Chord;
+                    other.hold = Hold::Chord;
+                } else {
+                    this.hold = Hold::Short;
+                }
+            } else {
+                if this.hold == Hold::Short {
+                    events[0] = Some(Event::Release(button));
+
+                    match this.last_click {
+                        Some(last) if now.wrapping_sub(last) <= Self::DOUBLE_CLICK => {
+                            events[1] = Some(Event::DoubleClick(button));
+                            this.last_click = None;
+                        },
+                        _ => this.last_click = Some(now),
+                    }
+                }
+
+                this.hold = Hold::Up;
+            }
+        } else if this.hold == Hold::Short && now.wrapping_sub(this.down_since) >= Self::LONG_PRESS {
+            events[0] = Some(Event::LongPress(button));
+            this.hold = Hold::Long;
+        }
+
+        for event in events.into_iter().flatten() {
+            self.push(event);
+        }
+    }
+
+    fn push(&mut self, event: Event) {
+        if self.len < Self::QUEUE_LEN {
+            self.queue[(self.head + self.len) % Self::QUEUE_LEN] = event;
+            self.len += 1;
+        }
+    }
+}
+
+impl Default for Buttons {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Samples `buttons` every millisecond from `from` up to `to` with the
+    ///  same levels, and takes every event that makes.
+    fn hold(buttons: &mut Buttons, from: u32, to: u32, a: bool, b: bool) -> Vec<Event> {
+        let mut events = Vec::new();
+        for now in from..to {
+            buttons.sample(now, a, b);
+            while let Some(event) = buttons.next_event() {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    #[test]
+    fn a_click_is_a_press_then_a_release() {
+        let mut buttons = Buttons::new();
+
+        assert_eq!(hold(&mut buttons, 0, 100, true, false), [Event::Press(Button::A)]);
+        assert_eq!(hold(&mut buttons, 100, 200, false, false), [Event::Release(Button::A)]);
+    }
+
+    #[test]
+    fn bounces_are_ignored() {
+        let mut buttons = Buttons::new();
+        let mut events = Vec::new();
+
+        for now in 0..100 {
+            // Chattering for the first few milliseconds, then steady.
+            buttons.sample(now, now > 10 || now % 2 == 0, false);
+            events.extend(core::iter::from_fn(|| buttons.next_event()));
+        }
+
+        assert_eq!(events, [Event::Press(Button::A)]);
+    }
+
+    #[test]
+    fn holding_on_makes_a_long_press() {
+        let mut buttons = Buttons::new();
+
+        assert_eq!(hold(&mut buttons, 0, 1000, false, true), [Event::Press(Button::B), Event::LongPress(Button::B)]);
+        assert_eq!(hold(&mut buttons, 1000, 1100, false, false), []);
+    }
+
+    #[test]
+    fn two_quick_clicks_make_a_double_click() {
+        let mut buttons = Buttons::new();
+        hold(&mut buttons, 0, 50, true, false);
+        hold(&mut buttons, 50, 100, false, false);
+        hold(&mut buttons, 100, 150, true, false);
+
+        assert_eq!(hold(&mut buttons, 150, 200, false, false), [Event::Release(Button::A), Event::DoubleClick(Button::A)]);
+
+        // A third click starts over.
+        hold(&mut buttons, 200, 250, true, false);
+        assert_eq!(hold(&mut buttons, 250, 300, false, false), [Event::Release(Button::A)]);
+    }
+
+    #[test]
+    fn slow_clicks_stay_single() {
+        let mut buttons = Buttons::new();
+        hold(&mut buttons, 0, 50, true, false);
+        hold(&mut buttons, 50, 500, false, false);
+        hold(&mut buttons, 500, 550, true, false);
+
+        assert_eq!(hold(&mut buttons, 550, 600, false, false), [Event::Release(Button::A)]);
+    }
+
+    #[test]
+    fn both_buttons_together_make_a_chord() {
+        let mut buttons = Buttons::new();
+
+        assert_eq!(hold(&mut buttons, 0, 50, true, false), [Event::Press(Button::A)]);
+        assert_eq!(hold(&mut buttons, 50, 100, true, true), [Event::Press(Button::B), Event::Chord]);
+        // Neither button clicks or long presses once it's part of a chord.
+        assert_eq!(hold(&mut buttons, 100, 1000, true, false), []);
+        assert_eq!(hold(&mut buttons, 1000, 1100, false, false), []);
+
+        assert_eq!(hold(&mut buttons, 1100, 1200, true, true), [Event::Press(Button::A), Event::Press(Button::B), Event::Chord]);
+    }
+
+    #[test]
+    fn time_can_wrap_around() {
+        let mut buttons = Buttons::new();
+        let start = u32::MAX - 10;
+
+        buttons.sample(start, true, false);
+        buttons.sample(start.wrapping_add(Buttons::DEBOUNCE), true, false);
+
+        assert_eq!(buttons.next_event(), Some(Event::Press(Button::A)));
+    }
+}
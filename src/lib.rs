@@ -0,0 +1,55 @@
+#![cfg_attr(not(test), no_std)]
+
+//! The cellular automaton engine, kept free of any board specifics so that it
+//!  builds for both the micro:bit and the host (where the tests run).
+
+pub mod app;
+pub mod automaton;
+pub mod boundary;
+pub mod console;
+pub mod display;
+pub mod editor;
+pub mod elementary;
+pub mod engine;
+pub mod font;
+pub mod generations;
+pub mod grid;
+pub mod history;
+pub mod input;
+pub mod library;
+pub mod neighborhood;
+pub mod options;
+pub mod plaintext;
+pub mod random;
+pub mod rle;
+pub mod rule;
+pub mod scroll;
+pub mod simulation;
+pub mod soup;
+pub mod stats;
+pub mod storage;
+pub mod viewport;
+pub mod wireworld;
+pub mod world;
+
+pub use automaton::Automaton;
+pub use boundary::Boundary;
+pub use console::{Command, Console};
+pub use elementary::Elementary;
+pub use engine::{conway_transitions, random_automata, update_automata, Transition};
+pub use generations::Generations;
+pub use grid::Grid;
+pub use history::{Fate, History, Reseed};
+pub use neighborhood::Neighborhood;
+pub use options::{Setting, Speed};
+pub use plaintext::Plaintext;
+pub use random::{Random, XorShift32};
+pub use rle::Rle;
+pub use rule::Rule;
+pub use scroll::Scroll;
+pub use simulation::{Hardware, Simulation};
+pub use soup::{Pattern, Soup};
+pub use stats::{Statistic, Stats};
+pub use storage::{Flash, Settings, Slot, Storage};
+pub use viewport::Viewport;
+pub use world::{BitGrid, Bounds, World};
@@ -0,0 +1,116 @@
+//! The demo patterns, compiled by `build.rs` from the files in `patterns/` into
+//!  a table that stays in flash.
+
+use crate::{generations::Generations, world::World};
+
+
+/// One of the demo patterns, with its cells packed a bit each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+    pub width: usize,
+    pub height: usize,
+    /// The rule the pattern is meant for, if its file says.
+    pub rule: Option<Generations>,
+    /// Whether each cell is alive, in reading order, eight to a byte and least
+    ///  significant bit first.
+    pub cells: &'static [u8],
+}
+
+impl Entry {
+    pub const fn get(&self, row: usize, col: usize) -> bool {
+        let bit = row * self.width + col;
+        self.cells[bit / 8] >> (bit % 8) & 1 == 1
+    }
+
+    /// Draws the pattern onto `world` with its top left at `row`, `col`,
+    ///  wrapping around the edges. Cells under its dead ones are cleared.
+    pub fn place<C: World>(&self, world: &mut C, row: usize, col: usize) {
+        for r in 0..self.height {
+            for c in 0..self.width {
+                world.set((row + r) % C::HEIGHT, (col + c) % C::WIDTH, self.get(r, c) as u8);
+            }
+        }
+    }
+
+    /// Draws the pattern onto `world` centred on `row`, `col`.
+    pub fn place_centred<C: World>(&self, world: &mut C, row: usize, col: usize) {
+        let top = (row + C::HEIGHT - (self.height / 2) % C::HEIGHT) % C::HEIGHT;
+        let left = (col + C::WIDTH - (self.width / 2) % C::WIDTH) % C::WIDTH;
+
+        self.place(world, top, left);
+    }
+}
+
+
+/// Every pattern in `patterns/`, in order of file name.
+pub static PATTERNS: &[Entry] = &include!(concat!(env!("OUT_DIR"), "/patterns.rs"));
+
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use super::*;
+    use crate::{grid::Grid, plaintext, rle, rule};
+
+    /// The pattern files `build.rs` picks up, in the order it takes them.
+    fn pattern_files() -> Vec<PathBuf> {
+        let mut files = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("patterns"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "rle" || extension == "cells"))
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn every_pattern_file_is_included() {
+        assert_eq!(PATTERNS.len(), pattern_files().len());
+
+        let glider = PATTERNS.iter().find(|entry| entry.name == "Glider").unwrap();
+        assert_eq!((glider.width, glider.height), (3, 3));
+        assert_eq!(glider.rule, Some(Generations::from_rule(rule::LIFE)));
+        assert_eq!(glider.cells, [0b1110_0010, 0b1]);
+
+        // Plaintext files have no rule, and are named in a comment.
+        let pulsar = PATTERNS.iter().find(|entry| entry.name == "Pulsar").unwrap();
+        assert_eq!(pulsar.rule, None);
+    }
+
+    #[test]
+    fn matches_the_patterns_as_read_at_runtime() {
+        for (entry, path) in PATTERNS.iter().zip(pattern_files()) {
+            let text = fs::read_to_string(&path).unwrap();
+
+            let mut expected = Grid::<32, 32>::new();
+            if path.extension().unwrap() == "rle" {
+                rle::read(&text, &mut expected, 0, 0).unwrap();
+            } else {
+                plaintext::read(&text, &mut expected, 0, 0).unwrap();
+            }
+
+            let mut placed = Grid::<32, 32>::new();
+            entry.place(&mut placed, 0, 0);
+            assert_eq!(placed, expected, "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn centres_and_wraps() {
+        let glider = PATTERNS.iter().find(|entry| entry.name == "Glider").unwrap();
+        let mut world = Grid::<4, 4>::new();
+        glider.place_centred(&mut world, 0, 0);
+
+        assert_eq!(world.into_rows(), [
+            [0, 1, 0, 0],
+            [1, 1, 0, 1],
+            [0, 0, 0, 0],
+            [1, 0, 0, 0],
+        ]);
+    }
+}
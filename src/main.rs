@@ -1,12 +1,26 @@
 #![no_std]
 #![no_main]
 
-use core::cell::RefCell;
+use core::{
+    cell::{Cell, RefCell},
+    fmt::{self, Write},
+};
 
 use cortex_m::interrupt::Mutex;
 use defmt_rtt as _;
 use panic_halt as _;
 
+use automata::{
+    app::{App, Effects},
+    console::{self, Command, Console, ParseCommandError},
+    display::Screen,
+    elementary,
+    input::Buttons,
+    rule,
+    simulation::{Report, TICK_PRESCALER},
+    storage::Flash,
+    Automaton, Fate, Generations, Grid, Hardware, Rle, Simulation, Storage, XorShift32,
+};
 use cortex_m_rt::entry;
 use embedded_hal::digital::InputPin;
 use microbit::{
@@ -14,83 +28,246 @@ use microbit::{
     display::nonblocking::{Display, GreyscaleImage},
     hal::{
         clocks::Clocks,
+        rng::Rng,
         rtc::{Rtc, RtcInterrupt},
+        timer::{Periodic, Timer},
+        uart::{Baudrate, Parity, Uart},
     },
-    pac::{self, interrupt, RTC0, TIMER1},
+    pac::{self, interrupt, NVMC, RTC0, RTC1, TIMER0, TIMER1, UART0},
 };
 
 
-enum State {
-    Running,
-    Paused,
-}
+const MODES: [Automaton; 3] = [
+    Automaton::Generations(Generations::from_rule(rule::LIFE)),
+    Automaton::Wireworld,
+    Automaton::Elementary(elementary::RULE_30),
+];
+
+
+/// The simulation runs on a world much larger than the display, which only
+///  ever shows the part of it under the viewport. Cells need a whole byte for
+///  the dying states of Generations rules and Wireworld.
+type Universe = Grid<32, 32>;
+
+/// The simulation, with the board's hardware under it.
+type Life = Simulation<Universe, Nvmc, Leds>;
 
 
 static DISPLAY: Mutex<RefCell<Option<Display<TIMER1>>>> = Mutex::new(RefCell::new(None));
+/// Counts microseconds from boot, to timestamp the logs and time the steps.
+static STOPWATCH: Mutex<RefCell<Option<Timer<TIMER0, Periodic>>>> = Mutex::new(RefCell::new(None));
 static ANIM_TIMER: Mutex<RefCell<Option<Rtc<RTC0>>>> = Mutex::new(RefCell::new(None));
+/// Whether RTC0 has ticked since the main loop last moved the simulation on.
+static TICKED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+/// Bytes from the serial port waiting for the main loop.
+static INBOX: Mutex<RefCell<Inbox>> = Mutex::new(RefCell::new(Inbox::new()));
 
-static mut IMAGE: [[u8; 5]; 5] = [[0; 5]; 5];
 
+/// Microseconds since boot, which wrap around after about 71 minutes.
+fn micros() -> u32 {
+    cortex_m::interrupt::free(|cs| STOPWATCH.borrow(cs).borrow().as_ref().map_or(0, |timer| timer.read()))
+}
 
-fn random_automata() -> [[u8; 5]; 5] {
-    static mut SEED: u16 = 39333;
-    let mut result = [[0; 5]; 5];
+defmt::timestamp!("{=u32:us}", micros());
 
-    unsafe {
-        let square = SEED as u32 * SEED as u32;
-        SEED = ((square << 8) >> 16) as u16;
-
-        let mut mask = 1;
-        for r in 0..5 {
-            for c in 0..5 {
-                result[r][c] = ((square & mask) >> (5 * r + c)) as u8;
-                mask <<= 1;
-            }
+
+/// Milliseconds since boot, near enough, for timing the buttons.
+struct Clock {
+    rtc: Rtc<RTC1>,
+    counter: u32,
+    millis: u32,
+}
+
+impl Clock {
+    /// The RTC counts up 32768 / 33 times a second, which is close enough to
+    ///  once a millisecond.
+    const PRESCALER: u32 = 32;
+
+    /// The counter only has 24 bits.
+    const COUNTER_MASK: u32 = 0xff_ffff;
+
+    fn new(rtc1: RTC1) -> Self {
+        let rtc = Rtc::new(rtc1, Self::PRESCALER).unwrap();
+        rtc.enable_counter();
+
+        Clock { rtc, counter: 0, millis: 0 }
+    }
+
+    fn millis(&mut self) -> u32 {
+        let counter = self.rtc.get_counter();
+        self.millis = self.millis.wrapping_add(counter.wrapping_sub(self.counter) & Self::COUNTER_MASK);
+        self.counter = counter;
+
+        self.millis
+    }
+}
+
+
+/// The bytes UART0's interrupt has taken off the serial port, which only holds
+///  six itself, so that a pasted pattern isn't lost while the world is being
+///  stepped. Anything past what fits is dropped.
+struct Inbox {
+    bytes: [u8; 256],
+    start: usize,
+    len: usize,
+}
+
+impl Inbox {
+    const fn new() -> Self {
+        Inbox { bytes: [0; 256], start: 0, len: 0 }
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.len < self.bytes.len() {
+            self.bytes[(self.start + self.len) % self.bytes.len()] = byte;
+            self.len += 1;
+        } else {
+            defmt::warn!("Dropped {=u8:#x} from the serial port", byte);
         }
-        
-        result
-    }
-}
-
-fn update_automata<F>(automata: [[u8; 5]; 5], transition_function: F) -> [[u8; 5]; 5]
-where
-    F: Fn(u8, [u8; 8]) -> u8
-{
-    let mut result = [[0; 5]; 5];
-
-    for row in 0..5 {
-        for col in 0..5 {
-            result[row][col] = transition_function(
-                automata[row][col],
-                [
-                    automata[(row + 4) % 5][(col + 4) % 5],     // Top left
-                    automata[(row + 4) % 5][col],               // Top middle
-                    automata[(row + 4) % 5][(col + 1) % 5],     // Top right
-                    
-                    automata[row][(col + 4) % 5],               // Middle left
-                    automata[row][(col + 1) % 5],               // Middle right
-                    
-                    automata[(row + 1) % 5][(col + 4) % 5],     // Bottom left
-                    automata[(row + 1) % 5][col],               // Bottom middle
-                    automata[(row + 1) % 5][(col + 1) % 5],     // Bottom right
-                ]
-            );
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        (self.len > 0).then(|| {
+            let byte = self.bytes[self.start];
+            self.start = (self.start + 1) % self.bytes.len();
+            self.len -= 1;
+            byte
+        })
+    }
+}
+
+
+/// The sending side of the serial port, which ends lines with the `\r\n`
+///  terminals expect.
+struct Serial(Uart<UART0>);
+
+impl fmt::Write for Serial {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.0.write_str("\r\n")?;
+            }
+            self.0.write_str(line)?;
         }
+
+        Ok(())
     }
+}
+
 
-    result
+/// The flash set aside for [`Storage`] in `memory.x`.
+struct Nvmc {
+    nvmc: NVMC,
+    start: usize,
 }
 
-fn conway_transitions(center_cell: u8, neighbors: [u8; 8]) -> u8 {
-    let live_neighbor_count = neighbors.iter().filter(|n| **n != 0).count();
+extern "C" {
+    static _storage_start: u32;
+    static _storage_end: u32;
+}
+
+impl Nvmc {
+    fn new(nvmc: NVMC) -> Self {
+        // Only the addresses of the linker's symbols mean anything.
+        let (start, end) = (&raw const _storage_start as usize, &raw const _storage_end as usize);
+        assert!(end - start >= Storage::<Self>::PAGES * Self::PAGE_SIZE);
 
-    match (center_cell, live_neighbor_count) {
-        (0, 3) => 1,
-        (1, 2..=3) => 1,
-        _ => 0,
+        Nvmc { nvmc, start }
+    }
+
+    fn wait(&self) {
+        while self.nvmc.ready.read().ready().is_busy() {}
     }
 }
 
+/// The CPU stalls while flash is written or erased, for up to about 20ms a
+///  page, which holds up the display for as long.
+impl Flash for Nvmc {
+    const PAGE_SIZE: usize = 1024;
+
+    fn read(&self, address: usize) -> u32 {
+        unsafe { ((self.start + address) as *const u32).read_volatile() }
+    }
+
+    fn erase(&mut self, page: usize) {
+        self.nvmc.config.write(|w| w.wen().een());
+        self.wait();
+        self.nvmc.erasepage().write(|w| unsafe { w.bits((self.start + page * Self::PAGE_SIZE) as u32) });
+        self.wait();
+        self.nvmc.config.write(|w| w.wen().ren());
+    }
+
+    fn write(&mut self, address: usize, word: u32) {
+        self.nvmc.config.write(|w| w.wen().wen());
+        self.wait();
+        unsafe { ((self.start + address) as *mut u32).write_volatile(word) };
+        self.wait();
+        self.nvmc.config.write(|w| w.wen().ren());
+    }
+}
+
+
+/// The LEDs, through TIMER1's display driver, and the ticks of RTC0.
+struct Leds {
+    /// What RTC0's prescaler is set to now, which can't be read back from it.
+    prescaler: u32,
+}
+
+impl Hardware for Leds {
+    fn show(&mut self, screen: &Screen) {
+        cortex_m::interrupt::free(|cs| {
+            if let Some(display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
+                display.show(&GreyscaleImage::from(*screen));
+            }
+        });
+    }
+
+    fn set_ticks(&mut self, prescaler: Option<u32>) {
+        cortex_m::interrupt::free(|cs| {
+            let mut timer = ANIM_TIMER.borrow(cs).borrow_mut();
+
+            // The prescaler can only be written while the counter is stopped,
+            //  and the HAL only writes it when taking the peripheral. The
+            //  events and interrupts enabled on it stay as they were. TIMER1
+            //  keeps the display going.
+            if let Some(prescaler) = prescaler.filter(|prescaler| *prescaler != self.prescaler) {
+                if let Some(rtc) = timer.take() {
+                    rtc.disable_counter();
+                    *timer = Some(Rtc::new(rtc.release(), prescaler).unwrap());
+                    self.prescaler = prescaler;
+                }
+            }
+
+            if let Some(rtc) = timer.as_mut() {
+                if prescaler.is_some() {
+                    rtc.enable_counter();
+                } else {
+                    rtc.disable_counter();
+                    rtc.reset_event(RtcInterrupt::Tick);
+                }
+            }
+        });
+    }
+
+    fn report(&mut self, report: Report) {
+        match report {
+            Report::Started(automaton) => defmt::info!("Starting {}", automaton),
+            Report::Placed(name, automaton) => defmt::info!("Placing {} under {}", name, automaton),
+            Report::Speed(speed) => defmt::info!("{} generations a second", speed.generations_per_second()),
+            Report::Settled(Fate::Extinct, from) => defmt::info!("Extinct at generation {}", from),
+            Report::Settled(Fate::StillLife, from) => defmt::info!("Still life from generation {}", from),
+            Report::Settled(Fate::Oscillator(period), from) => {
+                defmt::info!("Oscillator with period {} from generation {}", period, from)
+            },
+            Report::Saved(slot) => defmt::debug!("Saved to {}", slot),
+            Report::Loaded(slot) => defmt::debug!("Loaded {}", slot),
+            Report::Empty(slot) => defmt::debug!("Nothing in {}", slot),
+        }
+    }
+}
+
+
 #[entry]
 fn main() -> ! {
     let Some(mut board) = Board::take() else {
@@ -99,13 +276,29 @@ fn main() -> ! {
 
     Clocks::new(board.CLOCK).start_lfclk();
 
-    let mut rtc0 = Rtc::new(board.RTC0, 3000).unwrap();
+    let mut stopwatch = Timer::periodic(board.TIMER0);
+    stopwatch.start(u32::MAX);
+    cortex_m::interrupt::free(|cs| *STOPWATCH.borrow(cs).borrow_mut() = Some(stopwatch));
+
+    // Soups differ from one boot to the next; `XorShift32::default()` gives
+    //  the same ones every time instead.
+    let random = XorShift32::new(Rng::new(board.RNG).random_u32());
+
+    let mut rtc0 = Rtc::new(board.RTC0, TICK_PRESCALER).unwrap();
     rtc0.enable_event(RtcInterrupt::Tick);
     rtc0.enable_interrupt(RtcInterrupt::Tick, None);
     rtc0.enable_counter();
 
+    let mut clock = Clock::new(board.RTC1);
+
     let display = Display::new(board.TIMER1, board.display_pins);
 
+    // The interface chip passes the serial port through to USB. Bytes are
+    //  received by UART0's interrupt, as the HAL only waits for them.
+    let mut serial = Serial(Uart::new(board.UART0, board.uart.into(), Parity::EXCLUDED, Baudrate::BAUD115200));
+    unsafe { (*UART0::ptr()).intenset.write(|w| w.rxdrdy().set()) };
+    let mut console = Console::<1024>::new();
+
     cortex_m::interrupt::free(move |cs| {
         *DISPLAY.borrow(cs).borrow_mut() = Some(display);
         *ANIM_TIMER.borrow(cs).borrow_mut() = Some(rtc0);
@@ -114,105 +307,148 @@ fn main() -> ! {
     unsafe {
         board.NVIC.set_priority(pac::interrupt::RTC0, 64);
         board.NVIC.set_priority(pac::interrupt::TIMER1, 128);
+        board.NVIC.set_priority(pac::interrupt::UART0, 0);
         pac::NVIC::unmask(pac::interrupt::RTC0);
         pac::NVIC::unmask(pac::interrupt::TIMER1);
+        pac::NVIC::unmask(pac::interrupt::UART0);
     }
 
 
-    
-
+    // Pick up with the settings from before the reset, and the world too if
+    //  there was one, or start from the menu.
+    let storage = Storage::new(Nvmc::new(board.NVMC));
+    let mut life = Life::new(&MODES, storage, Leds { prescaler: TICK_PRESCALER }, random);
+    defmt::info!("Booted with {}", life.settings());
 
-    let mut automata = random_automata();
-    let mut state = State::Running;
-    let mut a_pressed = false;
-    let mut b_pressed = false;
+    let mut app = life.pick_up();
+    let mut buttons = Buttons::new();
 
-    unsafe { IMAGE = automata; }
+    app.start(&mut life);
+    let _ = write!(serial, "Type help for the commands.\n> ");
+    let mut last = 0;
 
     loop {
-        match state {
-            State::Paused => {
-                if !b_pressed {
-                    if let Ok(true) = board.buttons.button_b.is_low() {
-                        b_pressed = true;
-                        automata = update_automata(automata, conway_transitions);
-                        unsafe { IMAGE = automata; };
-                        cortex_m::interrupt::free(|cs| {
-                            if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
-                                rtc.reset_event(RtcInterrupt::Tick);
-                            }
-                            if let Some(mut display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
-                                draw(&mut display, automata, 7);
-                            }
-                        });
-                    }
-                }
+        let a = board.buttons.button_a.is_low() == Ok(true);
+        let b = board.buttons.button_b.is_low() == Ok(true);
+        buttons.sample(clock.millis(), a, b);
+
+        if cortex_m::interrupt::free(|cs| TICKED.borrow(cs).replace(false)) {
+            let started = micros();
+            if life.tick() {
+                let took = micros().wrapping_sub(started);
+                let stats = life.stats();
+                defmt::trace!("Generation {}: population {}, stepped in {=u32}us", stats.generation, stats.population, took);
+            }
+        }
 
-                if !a_pressed {
-                    if let Ok(true) = board.buttons.button_a.is_low() {
-                        a_pressed = true;
-                        // Should restart the timer so that interrupts are
-                        //  generated to drive the display.
-                        cortex_m::interrupt::free(|cs| {
-                            if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
-                                rtc.enable_counter();
-                            }
-                        });
-                        state = State::Running;
-                    }
-                }
-            },
+        if app.mode().is_running() && life.reseed_due() {
+            life.reseed();
+        }
 
-            State::Running => {
-                if !b_pressed {
-                    if let Ok(true) = board.buttons.button_b.is_low() {
-                        b_pressed = true;
-                        automata = random_automata();
-                        unsafe { IMAGE = automata; };
-                        cortex_m::interrupt::free(|cs| {
-                            if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
-                                rtc.reset_event(RtcInterrupt::Tick);
-                            }
-                            if let Some(mut display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
-                                draw(&mut display, automata, 7);
-                            }
-                        });
-                    }
-                }
+        while let Some(event) = buttons.next_event() {
+            let before = app.mode();
+            app.handle(event, &mut life);
 
-                if !a_pressed {
-                    if let Ok(true) = board.buttons.button_a.is_low() {
-                        a_pressed = true;
-                        // Should stop the timer so that no interrupts are 
-                        //  generated to drive the display.
-                        cortex_m::interrupt::free(|cs| {
-                            if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
-                                rtc.disable_counter();
-                                rtc.reset_event(RtcInterrupt::Tick);
-                            }
-                        });
-                        state = State::Paused;
-                    }
-                }
+            defmt::debug!("{} in {}", event, before);
+            if app.mode() != before {
+                defmt::debug!("{} -> {}", before, app.mode());
             }
-
         }
 
-        if let Ok(true) = board.buttons.button_a.is_high() { a_pressed = false; }
-        if let Ok(true) = board.buttons.button_b.is_high() { b_pressed = false; }
+        while let Some(byte) = cortex_m::interrupt::free(|cs| INBOX.borrow(cs).borrow_mut().pop()) {
+            // Terminals leave echoing to the other end, and send `\r`, `\n` or
+            //  both for Enter.
+            let line_ended = byte == b'\r' || byte == b'\n' && last != b'\r';
+            last = byte;
+            let _ = match byte {
+                b'\r' | b'\n' if line_ended => serial.write_str("\n"),
+                b'\r' | b'\n' => Ok(()),
+                0x08 | 0x7f => serial.write_str("\x08 \x08"),
+                _ => serial.write_char(byte as char),
+            };
+
+            if let Some(command) = console.push(byte) {
+                obey(command, &mut app, &mut life, &mut serial);
+            }
+            if line_ended {
+                let _ = serial.write_str(if console.is_loading() { "| " } else { "> " });
+            }
+        }
     }
 }
 
-fn draw(display: &mut Display<TIMER1>, mut automata: [[u8; 5]; 5], brightness: u8) {
-    for row in &mut automata {
-        for cell in row {
-            *cell *= brightness;
-        }
+
+/// Carries out a command from the serial console, and says how it went.
+fn obey(command: Result<Command, ParseCommandError>, app: &mut App, life: &mut Life, serial: &mut Serial) {
+    let command = match command {
+        Ok(command) => command,
+        Err(error) => {
+            defmt::warn!("Console: {}", error);
+            let _ = writeln!(serial, "Error: {error:?}");
+            return;
+        },
+    };
+    defmt::debug!("Console: {}", command);
+
+    // Commands that change the world leave the menus to show it.
+    if matches!(command, Command::Rule(_) | Command::Load(_) | Command::Seed(_)) && !app.mode().is_running() {
+        app.pause(life);
     }
 
-    display.show(&GreyscaleImage::new(&automata));
+    let _ = match command {
+        Command::Help => writeln!(serial, "{}", console::HELP),
+        Command::Pause => {
+            app.pause(life);
+            writeln!(serial, "Paused at generation {}", life.stats().generation)
+        },
+        Command::Run => {
+            app.resume(life);
+            writeln!(serial, "Running {}", life.settings().automaton)
+        },
+        Command::Step(generations) => {
+            app.pause(life);
+            for _ in 0..generations {
+                life.step();
+            }
+            let stats = life.stats();
+            writeln!(serial, "Generation {}, population {}", stats.generation, stats.population)
+        },
+        Command::Rule(rule) => {
+            life.set_rule(rule);
+            writeln!(serial, "Rule {rule}")
+        },
+        Command::Load(text) => match life.load_rle(text) {
+            Ok(header) => {
+                writeln!(serial, "Loaded {} by {}, rule {}", header.width, header.height, life.settings().automaton)
+            },
+            Err(error) => writeln!(serial, "Error: {error:?}"),
+        },
+        Command::Dump => {
+            let rule = match life.settings().automaton {
+                Automaton::Generations(rule) => Some(rule),
+                _ => None,
+            };
+            write!(serial, "{}", Rle::new(life.world(), rule))
+        },
+        Command::Speed(Some(speed)) => {
+            life.set_speed(speed);
+            writeln!(serial, "{}, {} generations a second", speed, speed.generations_per_second())
+        },
+        Command::Speed(None) => {
+            let speed = life.settings().speed;
+            writeln!(serial, "{}, {} generations a second", speed, speed.generations_per_second())
+        },
+        Command::Seed(seed) => {
+            match seed {
+                Some(seed) => life.reseed_from(seed),
+                None => life.reseed(),
+            }
+            writeln!(serial, "Population {}", life.stats().population)
+        },
+    };
 }
 
+
 #[interrupt]
 fn TIMER1() {
     cortex_m::interrupt::free(|cs| {
@@ -222,20 +458,29 @@ fn TIMER1() {
     });
 }
 
+/// Takes whatever has come in on the serial port. The HAL's `Uart` only
+///  sends, so the receiving side is left to this.
 #[interrupt]
-unsafe fn RTC0() {
-    cortex_m::interrupt::free(|cs| {
-        if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
-            rtc.reset_event(RtcInterrupt::Tick);
-        }
-    });
+fn UART0() {
+    let uart = unsafe { &*UART0::ptr() };
 
-    IMAGE = update_automata(IMAGE, conway_transitions);
-    let image = IMAGE;
+    while uart.events_rxdrdy.read().bits() != 0 {
+        uart.events_rxdrdy.reset();
+        let byte = uart.rxd.read().bits() as u8;
 
+        cortex_m::interrupt::free(|cs| INBOX.borrow(cs).borrow_mut().push(byte));
+    }
+}
+
+/// Only notes the tick for the main loop, which owns the simulation. Ticks that
+///  come while it's busy stepping, as they do when running flat out, are
+///  merged into one, so that it still gets to read the buttons.
+#[interrupt]
+fn RTC0() {
     cortex_m::interrupt::free(|cs| {
-        if let Some(mut display) = DISPLAY.borrow(cs).borrow_mut().as_mut() {
-            draw(&mut display, image, 7);
+        if let Some(rtc) = ANIM_TIMER.borrow(cs).borrow_mut().as_mut() {
+            rtc.reset_event(RtcInterrupt::Tick);
         }
+        TICKED.borrow(cs).set(true);
     });
-}
\ No newline at end of file
+}
@@ -0,0 +1,131 @@
+//! Which cells around a cell count as its neighbours.
+
+/// A set of cells within two rows and columns of a cell, stored as a 5x5 mask
+///  with bit `5 * (row + 2) + (col + 2)` set for each neighbour at `row`,
+///  `col` relative to the cell.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// The eight surrounding cells.
+    #[default]
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+    /// Six neighbours, emulating a hexagonal grid on a square one by skewing
+    ///  it: the Moore neighbourhood without the top right and bottom left.
+    Hexagonal,
+    /// Any neighbours within range two, see [`Neighborhood::custom`].
+    Custom(u32),
+}
+
+impl Neighborhood {
+    /// The most neighbours a cell can have, which is a full 5x5 mask.
+    pub const MAX_NEIGHBORS: usize = 24;
+
+    const CENTER: u32 = 1 << 12;
+
+    /// A neighbourhood from a picture of it, centred on the cell itself:
+    ///
+    /// ```
+    /// # use automata::neighborhood::Neighborhood;
+    /// const KNIGHT: Neighborhood = Neighborhood::custom([
+    ///     [0, 1, 0, 1, 0],
+    ///     [1, 0, 0, 0, 1],
+    ///     [0, 0, 0, 0, 0],
+    ///     [1, 0, 0, 0, 1],
+    ///     [0, 1, 0, 1, 0],
+    /// ]);
+    /// assert_eq!(KNIGHT.len(), 8);
+    /// ```
+    ///
+    /// The centre is ignored, as a cell's own state is always passed along
+    ///  separately.
+    pub const fn custom(mask: [[u8; 5]; 5]) -> Neighborhood {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < 25 {
+            if mask[i / 5][i % 5] != 0 {
+                bits |= 1 << i;
+            }
+            i += 1;
+        }
+
+        Neighborhood::Custom(bits & !Self::CENTER)
+    }
+
+    pub const fn mask(self) -> u32 {
+        match self {
+            Neighborhood::Moore => 0b00000_01110_01010_01110_00000,
+            Neighborhood::VonNeumann => 0b00000_00100_01010_00100_00000,
+            Neighborhood::Hexagonal => 0b00000_01100_01010_00110_00000,
+            Neighborhood::Custom(mask) => mask & !Self::CENTER,
+        }
+    }
+
+    /// How many neighbours every cell has.
+    pub const fn len(self) -> usize {
+        self.mask().count_ones() as usize
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.mask() == 0
+    }
+
+    /// Where the neighbours are relative to the cell, as `(row, col)`, in
+    ///  reading order from the top left to the bottom right.
+    pub fn offsets(self) -> impl Iterator<Item = (isize, isize)> {
+        let mask = self.mask();
+
+        (0..25)
+            .filter(move |i| mask >> i & 1 != 0)
+            .map(|i| (i / 5 - 2, i % 5 - 2))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moore_is_the_eight_surrounding_cells() {
+        let offsets = Neighborhood::Moore.offsets().collect::<Vec<_>>();
+
+        assert_eq!(offsets, [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn von_neumann_is_orthogonal() {
+        let offsets = Neighborhood::VonNeumann.offsets().collect::<Vec<_>>();
+
+        assert_eq!(offsets, [(-1, 0), (0, -1), (0, 1), (1, 0)]);
+    }
+
+    #[test]
+    fn hexagonal_skips_two_corners() {
+        let offsets = Neighborhood::Hexagonal.offsets().collect::<Vec<_>>();
+
+        assert_eq!(offsets, [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn custom_ignores_the_centre() {
+        let everything = Neighborhood::custom([[1; 5]; 5]);
+
+        assert_eq!(everything.len(), Neighborhood::MAX_NEIGHBORS);
+        assert!(everything.offsets().all(|offset| offset != (0, 0)));
+        assert_eq!(Neighborhood::custom([[0; 5]; 5]).len(), 0);
+    }
+
+    #[test]
+    fn custom_matches_the_built_in_shapes() {
+        let moore = Neighborhood::custom([
+            [0, 0, 0, 0, 0],
+            [0, 1, 1, 1, 0],
+            [0, 1, 1, 1, 0],
+            [0, 1, 1, 1, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+
+        assert_eq!(moore.mask(), Neighborhood::Moore.mask());
+    }
+}
@@ -0,0 +1,204 @@
+//! The settings that can be changed from the options menu, and how fast the
+//!  world runs.
+
+use core::fmt;
+
+
+/// One of the things the options menu changes.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Setting {
+    /// Which kind of automaton runs.
+    #[default]
+    Mode,
+    /// Which of the automaton's preset rules it runs.
+    Rule,
+    Speed,
+    Boundary,
+    /// How many live cells new soups start with.
+    Density,
+    /// How long a settled soup stays before a fresh one replaces it.
+    Reseed,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 6] = [
+        Setting::Mode,
+        Setting::Rule,
+        Setting::Speed,
+        Setting::Boundary,
+        Setting::Density,
+        Setting::Reseed,
+    ];
+
+    /// Where this is in [`Setting::ALL`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The name scrolled past when the setting is picked.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Setting::Mode => "Mode",
+            Setting::Rule => "Rule",
+            Setting::Speed => "Speed",
+            Setting::Boundary => "Edges",
+            Setting::Density => "Density",
+            Setting::Reseed => "Reseed",
+        }
+    }
+
+    /// The setting after this one, for cycling through them all.
+    pub const fn next(self) -> Setting {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+}
+
+
+/// How often the world moves on a generation, from slow motion to as fast as
+///  the board can go.
+///
+/// Each speed sets the prescaler of the RTC that ticks the world along, and
+///  the slowest also skip ticks, as even the largest prescaler ticks eight
+///  times a second.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Speed {
+    /// A generation a second.
+    SlowMotion,
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    /// Ticks faster than the world can be stepped, so that it steps flat out.
+    Fastest,
+}
+
+impl Speed {
+    pub const ALL: [Speed; 5] = [Speed::SlowMotion, Speed::Slow, Speed::Normal, Speed::Fast, Speed::Fastest];
+
+    /// How fast the RTC's low frequency clock runs, in hertz.
+    pub const CLOCK_HZ: u32 = 32_768;
+
+    /// Where this is in [`Speed::ALL`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The speed after this one, going from slowest to fastest and round again.
+    pub const fn next(self) -> Speed {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The next speed up, staying at the fastest.
+    pub const fn faster(self) -> Speed {
+        match self {
+            Speed::Fastest => Speed::Fastest,
+            _ => self.next(),
+        }
+    }
+
+    /// The next speed down, staying at the slowest.
+    pub const fn slower(self) -> Speed {
+        match self {
+            Speed::SlowMotion => Speed::SlowMotion,
+            _ => Self::ALL[self.index() - 1],
+        }
+    }
+
+    /// What the RTC's prescaler is set to, making it tick
+    ///  `CLOCK_HZ / (prescaler + 1)` times a second.
+    pub const fn prescaler(self) -> u32 {
+        match self {
+            Speed::SlowMotion | Speed::Slow => 4095,
+            Speed::Normal => 3000,
+            Speed::Fast => 1023,
+            Speed::Fastest => 255,
+        }
+    }
+
+    /// How many ticks each generation lasts.
+    pub const fn ticks_per_generation(self) -> u32 {
+        match self {
+            Speed::SlowMotion => 8,
+            Speed::Slow => 2,
+            _ => 1,
+        }
+    }
+
+    /// Roughly how many generations go by a second, if the world can keep up.
+    pub const fn generations_per_second(self) -> u32 {
+        Self::CLOCK_HZ / (self.prescaler() + 1) / self.ticks_per_generation()
+    }
+
+    /// Whether the world should step on tick number `tick`.
+    pub const fn steps_on(self, tick: u32) -> bool {
+        tick.is_multiple_of(self.ticks_per_generation())
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Speed::SlowMotion => "Slow motion",
+            Speed::Slow => "Slow",
+            Speed::Normal => "Normal",
+            Speed::Fast => "Fast",
+            Speed::Fastest => "Flat out",
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_cycle() {
+        let mut setting = Setting::default();
+        for expected in Setting::ALL.into_iter().skip(1) {
+            setting = setting.next();
+            assert_eq!(setting, expected);
+        }
+
+        assert_eq!(setting.next(), Setting::Mode);
+    }
+
+    #[test]
+    fn every_setting_has_a_name() {
+        for setting in Setting::ALL {
+            assert!(!setting.name().is_empty());
+        }
+    }
+
+    #[test]
+    fn speeds_get_faster_then_wrap() {
+        assert_eq!(Speed::SlowMotion.next(), Speed::Slow);
+        assert_eq!(Speed::Fastest.next(), Speed::SlowMotion);
+
+        for pair in Speed::ALL.windows(2) {
+            assert!(pair[0].generations_per_second() < pair[1].generations_per_second());
+        }
+        assert_eq!(Speed::SlowMotion.generations_per_second(), 1);
+    }
+
+    #[test]
+    fn faster_and_slower_stop_at_the_ends() {
+        assert_eq!(Speed::Normal.faster(), Speed::Fast);
+        assert_eq!(Speed::Normal.slower(), Speed::Slow);
+        assert_eq!(Speed::Fastest.faster(), Speed::Fastest);
+        assert_eq!(Speed::SlowMotion.slower(), Speed::SlowMotion);
+    }
+
+    #[test]
+    fn prescalers_fit_in_twelve_bits() {
+        assert!(Speed::ALL.iter().all(|speed| speed.prescaler() < 1 << 12));
+    }
+
+    #[test]
+    fn slow_speeds_skip_ticks() {
+        let steps = (0..8).filter(|tick| Speed::Slow.steps_on(*tick)).collect::<Vec<_>>();
+
+        assert_eq!(steps, [0, 2, 4, 6]);
+        assert!((0..8).all(|tick| Speed::Fast.steps_on(tick)));
+    }
+}
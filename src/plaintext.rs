@@ -0,0 +1,146 @@
+//! The plaintext `.cells` format: one line per row, `O` for a live cell and
+//!  `.` for a dead one, after any comment lines starting with `!`.
+//!
+//! ```text
+//! !Name: Glider
+//! .O
+//! ..O
+//! OOO
+//! ```
+
+use core::fmt;
+
+use crate::world::{Bounds, World};
+
+
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum ParsePlaintextError {
+    UnexpectedCharacter(u8),
+}
+
+
+/// Draws the pattern in `text` onto `world` with its top left at `row`, `col`,
+///  wrapping around the edges, and returns its width and height. Rows can be
+///  cut short, and the pattern is as wide as its longest row, all of which is
+///  cleared first. Nothing is drawn if `text` doesn't parse.
+pub fn read<C: World>(text: &str, world: &mut C, row: usize, col: usize) -> Result<(usize, usize), ParsePlaintextError> {
+    let (mut width, mut height) = (0, 0);
+    for (r, line) in rows(text).enumerate() {
+        if let Some(byte) = line.bytes().find(|byte| !matches!(byte, b'.' | b'O' | b'*')) {
+            return Err(ParsePlaintextError::UnexpectedCharacter(byte));
+        }
+
+        width = width.max(line.len());
+        height = r + 1;
+    }
+
+    for r in 0..height {
+        for c in 0..width {
+            world.set((row + r) % C::HEIGHT, (col + c) % C::WIDTH, 0);
+        }
+    }
+    for (r, line) in rows(text).enumerate() {
+        for (c, byte) in line.bytes().enumerate() {
+            if byte != b'.' {
+                world.set((row + r) % C::HEIGHT, (col + c) % C::WIDTH, 1);
+            }
+        }
+    }
+
+    Ok((width, height))
+}
+
+/// The rows of a pattern, without comments or trailing whitespace. Blank lines
+///  are empty rows.
+fn rows(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter(|line| !line.starts_with('!')).map(str::trim_end)
+}
+
+
+/// Writes the live part of a world as plaintext, through [`fmt::Display`],
+///  under a `!Name:` line if it's given one. Cells in any state but dead are
+///  written as live.
+#[derive(Clone, Copy, Debug)]
+pub struct Plaintext<'a, C> {
+    world: &'a C,
+    name: Option<&'a str>,
+}
+
+impl<'a, C: World> Plaintext<'a, C> {
+    pub const fn new(world: &'a C, name: Option<&'a str>) -> Self {
+        Plaintext { world, name }
+    }
+}
+
+impl<C: World> fmt::Display for Plaintext<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name {
+            writeln!(f, "!Name: {name}")?;
+        }
+
+        let Some(bounds) = Bounds::of(self.world) else { return Ok(()) };
+
+        for row in bounds.row..bounds.row + bounds.height {
+            for col in bounds.col..bounds.col + bounds.width {
+                f.write_str(if self.world.get(row, col) != 0 { "O" } else { "." })?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    const PULSAR: &str = include_str!("../patterns/pulsar.cells");
+
+    #[test]
+    fn reads_the_pulsar() {
+        let mut world = Grid::<32, 32>::new();
+
+        assert_eq!(read(PULSAR, &mut world, 10, 10), Ok((13, 13)));
+        assert_eq!(world.iter().filter(|cell| **cell == 1).count(), 48);
+        assert_eq!(world[(10, 12)], 1);
+        assert_eq!(world[(12, 10)], 1);
+    }
+
+    #[test]
+    fn short_rows_and_wrapping() {
+        let mut world = Grid::<3, 3>::from_rows([[1; 3]; 3]);
+
+        assert_eq!(read("!Blinker, on its side\n\n*\n.O", &mut world, 2, 2), Ok((2, 3)));
+        // The pattern covers the last column and wraps onto the first, which
+        //  leaves the middle one as it was.
+        assert_eq!(world.into_rows(), [
+            [0, 1, 1],
+            [1, 1, 0],
+            [0, 1, 0],
+        ]);
+    }
+
+    #[test]
+    fn bad_patterns_draw_nothing() {
+        let mut world = Grid::<3, 3>::new();
+
+        assert_eq!(read(".O\nOx", &mut world, 0, 0), Err(ParsePlaintextError::UnexpectedCharacter(b'x')));
+        assert_eq!(world, Grid::new());
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut world = Grid::<32, 32>::new();
+        read(PULSAR, &mut world, 3, 5).unwrap();
+
+        let text = Plaintext::new(&world, Some("Pulsar")).to_string();
+        assert!(text.starts_with("!Name: Pulsar\n..OOO...OOO..\n"));
+
+        let mut back = Grid::<32, 32>::new();
+        read(&text, &mut back, 3, 5).unwrap();
+        assert_eq!(back, world);
+    }
+}
@@ -0,0 +1,85 @@
+//! Pseudo-random numbers for seeding worlds.
+
+/// A source of random bits.
+pub trait Random {
+    fn next_u32(&mut self) -> u32;
+}
+
+
+/// Marsaglia's xorshift32: tiny, fast, and it goes through every non-zero
+///  `u32` before repeating.
+///
+/// The same seed always gives the same numbers, so tests can pin one down,
+///  while the firmware seeds it from the hardware RNG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XorShift32 {
+    state: u32,
+}
+
+impl XorShift32 {
+    /// Xorshift gets stuck at zero, so that seed is swapped for this one.
+    pub const DEFAULT_SEED: u32 = 39333;
+
+    pub const fn new(seed: u32) -> Self {
+        XorShift32 { state: if seed == 0 { Self::DEFAULT_SEED } else { seed } }
+    }
+}
+
+impl Default for XorShift32 {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SEED)
+    }
+}
+
+impl Random for XorShift32 {
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+
+        x
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = XorShift32::new(1234);
+        let mut b = XorShift32::new(1234);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn zero_seed_still_produces_numbers() {
+        let mut random = XorShift32::new(0);
+
+        assert_eq!(random, XorShift32::default());
+        assert_ne!(random.next_u32(), 0);
+    }
+
+    #[test]
+    fn matches_reference_values() {
+        // The first two steps of the 13/17/5 shifts from a seed of one.
+        let mut random = XorShift32::new(1);
+
+        assert_eq!(random.next_u32(), 270369);
+        assert_eq!(random.next_u32(), 67634689);
+    }
+
+    #[test]
+    fn bits_are_roughly_balanced() {
+        let mut random = XorShift32::new(42);
+        let ones: u32 = (0..1000).map(|_| random.next_u32().count_ones()).sum();
+
+        assert!((15_000..17_000).contains(&ones), "{ones}");
+    }
+}
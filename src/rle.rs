@@ -0,0 +1,386 @@
+//! The run length encoded format most Life patterns are shared in.
+//!
+//! A pattern is a header giving its size and rule, then its rows as runs of
+//!  cells, `b` for dead and `o` for alive, or `.` and `A` to `X` (and beyond,
+//!  with a prefix from `p` to `y`) when there are more than two states. Rows
+//!  end with `$` and the pattern with `!`:
+//!
+//! ```text
+//! #N Glider
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+
+use core::fmt;
+
+use crate::{
+    generations::Generations,
+    rule::ParseRuleError,
+    world::{Bounds, World},
+};
+
+
+/// What the header line says about a pattern.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub struct Header {
+    pub width: usize,
+    pub height: usize,
+    /// The rule the pattern is meant for, if it says.
+    pub rule: Option<Generations>,
+}
+
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum ParseRleError {
+    /// There's no `x = ..., y = ...` line before the cells.
+    MissingHeader,
+    MalformedHeader,
+    InvalidRule(ParseRuleError),
+    UnexpectedCharacter(u8),
+    /// A state past 255.
+    InvalidState,
+}
+
+impl From<ParseRuleError> for ParseRleError {
+    fn from(error: ParseRuleError) -> Self {
+        ParseRleError::InvalidRule(error)
+    }
+}
+
+
+/// Draws the pattern in `text` onto `world` with its top left at `row`, `col`,
+///  wrapping around the edges, and returns its header. Cells within the size
+///  the header gives are cleared first. Nothing is drawn if `text` doesn't
+///  parse, and nothing past the size of `world`, however large the header
+///  says the pattern is.
+pub fn read<C: World>(text: &str, world: &mut C, row: usize, col: usize) -> Result<Header, ParseRleError> {
+    let (header, cells) = split(text)?;
+    let clipped = Header { width: header.width.min(C::WIDTH), height: header.height.min(C::HEIGHT), ..header };
+    parse_cells(cells, clipped, |_, _, _| {})?;
+
+    for r in 0..clipped.height {
+        for c in 0..clipped.width {
+            world.set((row + r) % C::HEIGHT, (col + c) % C::WIDTH, 0);
+        }
+    }
+    parse_cells(cells, clipped, |r, c, state| world.set((row + r) % C::HEIGHT, (col + c) % C::WIDTH, state))?;
+
+    Ok(header)
+}
+
+/// The header of the pattern in `text`, and the text of its cells.
+fn split(text: &str) -> Result<(Header, &str), ParseRleError> {
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        let line = line.trim();
+        rest = next;
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('x') {
+            return Ok((parse_header(line)?, rest));
+        }
+
+        break;
+    }
+
+    Err(ParseRleError::MissingHeader)
+}
+
+/// Parses `x = 3, y = 3, rule = B3/S23`, where the rule is optional and any
+///  other keys are skipped over. The rule always comes last, and takes the
+///  rest of the line.
+fn parse_header(line: &str) -> Result<Header, ParseRleError> {
+    let (sizes, rule) = match line.find("rule") {
+        Some(at) => (&line[..at], Some(&line[at..])),
+        None => (line, None),
+    };
+
+    let (mut width, mut height) = (None, None);
+    for part in sizes.split(',').filter(|part| !part.trim().is_empty()) {
+        let (key, value) = part.split_once('=').ok_or(ParseRleError::MalformedHeader)?;
+        let value = value.trim();
+
+        match key.trim() {
+            "x" => width = Some(value.parse().map_err(|_| ParseRleError::MalformedHeader)?),
+            "y" => height = Some(value.parse().map_err(|_| ParseRleError::MalformedHeader)?),
+            _ => {},
+        }
+    }
+
+    let rule = match rule {
+        Some(rule) => {
+            let (_, rule) = rule.split_once('=').ok_or(ParseRleError::MalformedHeader)?;
+            // Golly adds the shape of bounded worlds after a colon.
+            Some(Generations::parse(rule.split(':').next().unwrap_or(rule).trim())?)
+        },
+        None => None,
+    };
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok(Header { width, height, rule }),
+        _ => Err(ParseRleError::MalformedHeader),
+    }
+}
+
+/// Goes through the runs of `cells`, calling `draw` with the row, column and
+///  state of every cell that isn't dead and lies within the header's size.
+fn parse_cells(cells: &str, header: Header, mut draw: impl FnMut(usize, usize, u8)) -> Result<(), ParseRleError> {
+    let (mut row, mut col): (usize, usize) = (0, 0);
+    let mut count: Option<usize> = None;
+    let mut prefix = None;
+
+    for byte in cells.bytes() {
+        let run = count.unwrap_or(1);
+
+        let state = match byte {
+            b'0'..=b'9' => {
+                count = Some(count.unwrap_or(0).saturating_mul(10).saturating_add((byte - b'0') as usize));
+                continue;
+            },
+            b'p'..=b'y' if prefix.is_none() => {
+                prefix = Some((byte - b'p' + 1) as usize);
+                continue;
+            },
+            b'A'..=b'X' => prefix.take().unwrap_or(0) * 24 + (byte - b'A' + 1) as usize,
+            _ if prefix.is_some() => return Err(ParseRleError::UnexpectedCharacter(byte)),
+            b'b' | b'.' => 0,
+            b'o' => 1,
+            b'$' => {
+                row = row.saturating_add(run);
+                col = 0;
+                count = None;
+                continue;
+            },
+            b'!' => return Ok(()),
+            _ if byte.is_ascii_whitespace() => continue,
+            _ => return Err(ParseRleError::UnexpectedCharacter(byte)),
+        };
+
+        let state = u8::try_from(state).map_err(|_| ParseRleError::InvalidState)?;
+        if state != 0 && row < header.height {
+            for c in col..col.saturating_add(run).min(header.width) {
+                draw(row, c, state);
+            }
+        }
+
+        col = col.saturating_add(run);
+        count = None;
+    }
+
+    Ok(())
+}
+
+
+/// Writes the live part of a world as RLE, through [`fmt::Display`], with the
+///  rule in the header if it's given. The states are written as letters if
+///  any cell is past state 1, and lines are kept to 70 characters.
+#[derive(Clone, Copy, Debug)]
+pub struct Rle<'a, C> {
+    world: &'a C,
+    rule: Option<Generations>,
+}
+
+impl<'a, C: World> Rle<'a, C> {
+    pub const fn new(world: &'a C, rule: Option<Generations>) -> Self {
+        Rle { world, rule }
+    }
+}
+
+impl<C: World> fmt::Display for Rle<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = Bounds::of(self.world).unwrap_or(Bounds { row: 0, col: 0, height: 0, width: 0 });
+        let cell = |row: usize, col: usize| self.world.get(bounds.row + row, bounds.col + col);
+
+        write!(f, "x = {}, y = {}", bounds.width, bounds.height)?;
+        if let Some(rule) = self.rule {
+            write!(f, ", rule = {rule}")?;
+        }
+        writeln!(f)?;
+
+        let multistate = (0..bounds.height).any(|row| (0..bounds.width).any(|col| cell(row, col) > 1));
+        let mut line = Line { f, len: 0 };
+        let mut rows_ended = 0;
+
+        for row in 0..bounds.height {
+            // Dead cells at the end of a row are left off.
+            let Some(last) = (0..bounds.width).rev().find(|col| cell(row, *col) != 0) else {
+                rows_ended += 1;
+                continue;
+            };
+
+            if row > 0 {
+                line.run(rows_ended + 1, '$', None)?;
+            }
+            rows_ended = 0;
+
+            let mut col = 0;
+            while col <= last {
+                let state = cell(row, col);
+                let run = (col..=last).take_while(|c| cell(row, *c) == state).count();
+
+                line.run(run, tag(state, multistate), prefix(state, multistate))?;
+                col += run;
+            }
+        }
+
+        line.run(1, '!', None)?;
+        writeln!(line.f)
+    }
+}
+
+/// Writes runs, starting a new line before any that would go past 70
+///  characters.
+struct Line<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    len: usize,
+}
+
+impl Line<'_, '_> {
+    const MAX_LEN: usize = 70;
+
+    fn run(&mut self, count: usize, tag: char, prefix: Option<char>) -> fmt::Result {
+        let digits = if count > 1 { count.ilog10() as usize + 1 } else { 0 };
+        let len = digits + prefix.is_some() as usize + 1;
+
+        if self.len + len > Self::MAX_LEN {
+            writeln!(self.f)?;
+            self.len = 0;
+        }
+        self.len += len;
+
+        if count > 1 {
+            write!(self.f, "{count}")?;
+        }
+        if let Some(prefix) = prefix {
+            write!(self.f, "{prefix}")?;
+        }
+        write!(self.f, "{tag}")
+    }
+}
+
+/// The letter for `state`, after its prefix if it has one.
+fn tag(state: u8, multistate: bool) -> char {
+    match (state, multistate) {
+        (0, false) => 'b',
+        (_, false) => 'o',
+        (0, true) => '.',
+        (_, true) => (b'A' + (state - 1) % 24) as char,
+    }
+}
+
+fn prefix(state: u8, multistate: bool) -> Option<char> {
+    (multistate && state > 24).then(|| (b'p' + (state - 25) / 24) as char)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generations::BRIANS_BRAIN, grid::Grid, rule};
+
+    const GLIDER: &str = include_str!("../patterns/glider.rle");
+
+    #[test]
+    fn reads_the_glider() {
+        let mut world = Grid::<5, 5>::new();
+        let header = read(GLIDER, &mut world, 1, 1).unwrap();
+
+        assert_eq!(header, Header { width: 3, height: 3, rule: Some(Generations::from_rule(rule::LIFE)) });
+        assert_eq!(world.into_rows(), [
+            [0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0],
+            [0, 0, 0, 1, 0],
+            [0, 1, 1, 1, 0],
+            [0, 0, 0, 0, 0],
+        ]);
+    }
+
+    #[test]
+    fn reads_the_lightweight_spaceship() {
+        let mut world = Grid::<32, 32>::new();
+        let header = read(include_str!("../patterns/lwss.rle"), &mut world, 0, 0).unwrap();
+
+        assert_eq!((header.width, header.height), (5, 4));
+        assert_eq!(world.iter().filter(|cell| **cell == 1).count(), 9);
+    }
+
+    #[test]
+    fn reads_every_state_and_wraps() {
+        let mut world = Grid::<4, 4>::new();
+        let header = read("x = 3, y = 2, rule = B2/S/C3:T4,4\n.AB$\npAo$3o!", &mut world, 2, 2).unwrap();
+
+        assert_eq!(header.rule, Some(BRIANS_BRAIN));
+        // The last row is past the height the header gives.
+        assert_eq!(world.into_rows(), [
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [2, 0, 0, 1],
+            [0, 0, 25, 1],
+        ]);
+    }
+
+    #[test]
+    fn bad_patterns_draw_nothing() {
+        let mut world = Grid::<4, 4>::new();
+
+        assert_eq!(read("bo$ob!", &mut world, 0, 0), Err(ParseRleError::MissingHeader));
+        assert_eq!(read("x = 2\nbo!", &mut world, 0, 0), Err(ParseRleError::MalformedHeader));
+        assert_eq!(read("x = 2, y = 1, rule = B9\no!", &mut world, 0, 0), Err(ParseRleError::InvalidRule(ParseRuleError::InvalidCount(9))));
+        assert_eq!(read("x = 2, y = 1\noz!", &mut world, 0, 0), Err(ParseRleError::UnexpectedCharacter(b'z')));
+        assert_eq!(read("x = 2, y = 1\nyX!", &mut world, 0, 0), Err(ParseRleError::InvalidState));
+        assert_eq!(world, Grid::new());
+    }
+
+    #[test]
+    fn huge_patterns_are_cut_to_the_world() {
+        let mut world = Grid::<8, 8>::new();
+        let header = read("x = 4000000000, y = 4000000000\n4000000000o$4000000000o!", &mut world, 0, 0).unwrap();
+
+        assert_eq!((header.width, header.height), (4_000_000_000, 4_000_000_000));
+        assert_eq!(world.into_rows()[..3], [[1; 8], [1; 8], [0; 8]]);
+    }
+
+    #[test]
+    fn writes_the_live_part() {
+        let mut world = Grid::<8, 8>::new();
+        read(GLIDER, &mut world, 4, 2).unwrap();
+
+        assert_eq!(Rle::new(&world, Some(Generations::from_rule(rule::LIFE))).to_string(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(Rle::new(&Grid::<8, 8>::new(), None).to_string(), "x = 0, y = 0\n!\n");
+    }
+
+    #[test]
+    fn round_trips_multistate_patterns() {
+        let mut world = Grid::<32, 32>::new();
+        world.set(0, 0, 2);
+        world.set(3, 1, 30);
+        world.set(3, 31, 1);
+
+        let text = Rle::new(&world, Some(BRIANS_BRAIN)).to_string();
+        assert_eq!(text, "x = 32, y = 4, rule = B2/S/C3\nB3$.pF29.A!\n");
+
+        let mut back = Grid::<32, 32>::new();
+        read(&text, &mut back, 0, 0).unwrap();
+        assert_eq!(back, world);
+    }
+
+    #[test]
+    fn long_lines_are_broken() {
+        let mut world = Grid::<32, 32>::new();
+        for col in (0..32).step_by(2) {
+            for row in 0..32 {
+                world.set(row, col + row % 2, 1);
+            }
+        }
+
+        let text = Rle::new(&world, None).to_string();
+        assert!(text.lines().all(|line| line.len() <= 70));
+
+        let mut back = Grid::<32, 32>::new();
+        read(&text, &mut back, 0, 0).unwrap();
+        assert_eq!(back, world);
+    }
+}
@@ -0,0 +1,269 @@
+//! Life-like (outer totalistic) rules, written in the usual `B3/S23` notation.
+
+use core::{fmt, str::FromStr};
+
+use crate::engine::Transition;
+
+
+pub const LIFE: Rule = Rule::new("B3/S23");
+pub const HIGHLIFE: Rule = Rule::new("B36/S23");
+pub const SEEDS: Rule = Rule::new("B2/S");
+pub const DAY_AND_NIGHT: Rule = Rule::new("B3678/S34678");
+pub const LIFE_WITHOUT_DEATH: Rule = Rule::new("B3/S012345678");
+pub const REPLICATOR: Rule = Rule::new("B1357/S1357");
+pub const MAZE: Rule = Rule::new("B3/S12345");
+
+/// The well known rules, by name.
+pub const PRESETS: [(&str, Rule); 7] = [
+    ("Life", LIFE),
+    ("HighLife", HIGHLIFE),
+    ("Seeds", SEEDS),
+    ("Day & Night", DAY_AND_NIGHT),
+    ("Life without Death", LIFE_WITHOUT_DEATH),
+    ("Replicator", REPLICATOR),
+    ("Maze", MAZE),
+];
+
+
+/// A two-state rule where whether a cell is born or survives depends only on
+///  how many of its eight neighbours are alive.
+///
+/// Bit `n` of `birth` is set if a dead cell with `n` live neighbours comes to
+///  life, and bit `n` of `survival` if a live cell with `n` stays alive.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub birth: u16,
+    pub survival: u16,
+}
+
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum ParseRuleError {
+    /// A neighbour count outside `0..=8`.
+    InvalidCount(u8),
+    UnexpectedCharacter(u8),
+    /// The same section appears twice, as in `B3/B6`.
+    RepeatedSection,
+    /// Neither `B`/`S` notation nor the legacy `survival/birth` form.
+    Malformed,
+    /// A Generations rule needs at least two states, and at most 255.
+    InvalidStates,
+}
+
+impl Rule {
+    /// Parses a rule at compile time, so it can be baked into flash.
+    ///
+    /// # Panics
+    /// If `rule` isn't a valid rule string, which fails the build when used in
+    ///  a `const`.
+    pub const fn new(rule: &str) -> Rule {
+        match Rule::parse(rule) {
+            Ok(rule) => rule,
+            Err(_) => panic!("invalid rule string"),
+        }
+    }
+
+    /// Parses `B3/S23` style rules, with the sections in either order and the
+    ///  slash optional, as well as the legacy `23/3` (survival/birth) form.
+    pub const fn parse(rule: &str) -> Result<Rule, ParseRuleError> {
+        Rule::parse_bytes(rule.as_bytes())
+    }
+
+    pub(crate) const fn parse_bytes(bytes: &[u8]) -> Result<Rule, ParseRuleError> {
+        let mut birth = None;
+        let mut survival = None;
+        let mut legacy = [0u16; 2];
+        let mut legacy_section = 0;
+        // Whether any counts came before a `B` or `S`, which only the legacy
+        //  form has.
+        let mut legacy_counts = false;
+        let mut current: Option<bool> = None;   // `Some(true)` while reading births
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let byte = bytes[i];
+            i += 1;
+
+            match byte {
+                b'B' | b'b' | b'S' | b's' => {
+                    let is_birth = byte == b'B' || byte == b'b';
+                    if (is_birth && birth.is_some()) || (!is_birth && survival.is_some()) {
+                        return Err(ParseRuleError::RepeatedSection);
+                    }
+
+                    if is_birth {
+                        birth = Some(0);
+                    } else {
+                        survival = Some(0);
+                    }
+                    current = Some(is_birth);
+                },
+
+                b'/' => {
+                    if current.is_none() {
+                        legacy_section += 1;
+                        if legacy_section > 1 {
+                            return Err(ParseRuleError::Malformed);
+                        }
+                    }
+                },
+
+                b'0'..=b'9' => {
+                    let count = byte - b'0';
+                    if count > 8 {
+                        return Err(ParseRuleError::InvalidCount(count));
+                    }
+
+                    match (current, birth, survival) {
+                        (Some(true), Some(counts), _) => birth = Some(counts | 1 << count),
+                        (Some(false), _, Some(counts)) => survival = Some(counts | 1 << count),
+                        _ => {
+                            legacy[legacy_section] |= 1 << count;
+                            legacy_counts = true;
+                        },
+                    }
+                },
+
+                _ => return Err(ParseRuleError::UnexpectedCharacter(byte)),
+            }
+        }
+
+        match (birth, survival) {
+            (Some(birth), Some(survival)) if legacy_section == 0 && !legacy_counts => Ok(Rule { birth, survival }),
+            (None, None) if legacy_section == 1 => Ok(Rule { birth: legacy[1], survival: legacy[0] }),
+            _ => Err(ParseRuleError::Malformed),
+        }
+    }
+
+    /// Counts past eight, which only larger neighbourhoods can reach, never
+    ///  give birth.
+    pub const fn is_born(&self, live_neighbors: usize) -> bool {
+        live_neighbors <= 8 && self.birth >> live_neighbors & 1 != 0
+    }
+
+    pub const fn survives(&self, live_neighbors: usize) -> bool {
+        live_neighbors <= 8 && self.survival >> live_neighbors & 1 != 0
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        LIFE
+    }
+}
+
+impl Transition for Rule {
+    fn next(&self, center_cell: u8, neighbors: &[u8]) -> u8 {
+        let live_neighbor_count = neighbors.iter().filter(|n| **n != 0).count();
+
+        let alive = if center_cell == 0 {
+            self.is_born(live_neighbor_count)
+        } else {
+            self.survives(live_neighbor_count)
+        };
+
+        alive as u8
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        Rule::parse(rule)
+    }
+}
+
+/// Writes the rule in `B3/S23` notation.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("B")?;
+        for count in 0..=8 {
+            if self.is_born(count) {
+                write!(f, "{count}")?;
+            }
+        }
+
+        f.write_str("/S")?;
+        for count in 0..=8 {
+            if self.survives(count) {
+                write!(f, "{count}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conway_transitions, engine::update_automata, grid::Grid, Boundary, Neighborhood};
+
+    #[test]
+    fn parses_birth_survival_notation() {
+        assert_eq!(Rule::parse("B3/S23"), Ok(Rule { birth: 0b1000, survival: 0b1100 }));
+        assert_eq!(Rule::parse("b36s23"), Ok(HIGHLIFE));
+        assert_eq!(Rule::parse("S23/B3"), Ok(LIFE));
+        assert_eq!(Rule::parse("B2/S"), Ok(Rule { birth: 0b100, survival: 0 }));
+    }
+
+    #[test]
+    fn parses_legacy_notation() {
+        assert_eq!(Rule::parse("23/3"), Ok(LIFE));
+        assert_eq!(Rule::parse("/2"), Ok(SEEDS));
+        assert_eq!("34678/3678".parse(), Ok(DAY_AND_NIGHT));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        assert_eq!(Rule::parse("B9/S23"), Err(ParseRuleError::InvalidCount(9)));
+        assert_eq!(Rule::parse("B3/S2x"), Err(ParseRuleError::UnexpectedCharacter(b'x')));
+        assert_eq!(Rule::parse("B3/B6"), Err(ParseRuleError::RepeatedSection));
+        assert_eq!(Rule::parse("B3"), Err(ParseRuleError::Malformed));
+        assert_eq!(Rule::parse("23"), Err(ParseRuleError::Malformed));
+        assert_eq!(Rule::parse("2/3/4"), Err(ParseRuleError::Malformed));
+        // Legacy counts can't be mixed with `B` and `S` sections.
+        assert_eq!(Rule::parse("2B3/S23"), Err(ParseRuleError::Malformed));
+        assert_eq!(Rule::parse(""), Err(ParseRuleError::Malformed));
+    }
+
+    #[test]
+    fn displays_in_birth_survival_notation() {
+        assert_eq!(LIFE_WITHOUT_DEATH.to_string(), "B3/S012345678");
+        assert_eq!(SEEDS.to_string(), "B2/S");
+
+        for (_, rule) in PRESETS {
+            assert_eq!(rule.to_string().parse(), Ok(rule));
+        }
+    }
+
+    #[test]
+    fn life_matches_conway_transitions() {
+        let mut automata = Grid::<8, 8>::new();
+        for (row, col) in [(1, 2), (2, 3), (3, 1), (3, 2), (3, 3), (5, 5), (5, 6), (6, 5)] {
+            automata[(row, col)] = 1;
+        }
+
+        let mut by_rule = automata;
+        for _ in 0..10 {
+            automata = update_automata(&automata, Boundary::Torus, Neighborhood::Moore, conway_transitions);
+            by_rule = update_automata(&by_rule, Boundary::Torus, Neighborhood::Moore, LIFE);
+            assert_eq!(by_rule, automata);
+        }
+    }
+
+    #[test]
+    fn highlife_births_on_six() {
+        let neighbors = [1, 1, 1, 1, 1, 1, 0, 0];
+
+        assert_eq!(HIGHLIFE.next(0, &neighbors), 1);
+        assert_eq!(LIFE.next(0, &neighbors), 0);
+    }
+
+    #[test]
+    fn seeds_never_survive() {
+        assert_eq!(SEEDS.next(1, &[1, 1, 0, 0, 0, 0, 0, 0]), 0);
+        assert_eq!(SEEDS.next(0, &[1, 1, 0, 0, 0, 0, 0, 0]), 1);
+    }
+}
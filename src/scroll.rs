@@ -0,0 +1,164 @@
+//! Text scrolling across the LED matrix, one column per tick, so that it can
+//!  be driven from a timer interrupt instead of blocking until it's done.
+
+use core::fmt::{self, Write};
+
+use crate::{
+    display::{Screen, DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    font::{glyph, Glyph},
+};
+
+
+/// A line of text moving right to left across the display, from coming in at
+///  the right edge until it's gone past the left one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scroll {
+    text: [char; Self::MAX_LEN],
+    len: usize,
+    /// How many columns the text has moved in from the right edge.
+    offset: usize,
+}
+
+impl Scroll {
+    /// The most characters a scroll holds; any more are left off.
+    pub const MAX_LEN: usize = 32;
+
+    /// A scroll of `text` as it's displayed, so numbers can be written with
+    ///  `format_args!`.
+    pub fn new(text: impl fmt::Display) -> Self {
+        let mut scroll = Scroll { text: [' '; Self::MAX_LEN], len: 0, offset: 0 };
+
+        // Overflowing is the only way writing can fail, and then the text is
+        //  just cut short.
+        let _ = write!(scroll, "{text}");
+
+        scroll
+    }
+
+    /// The text being scrolled.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.text[..self.len].iter().copied()
+    }
+
+    /// How many columns wide the text is, with one blank column after every
+    ///  character.
+    pub fn width(&self) -> usize {
+        self.chars().map(|c| glyph(c).width + 1).sum()
+    }
+
+    /// Whether the text has scrolled all the way off the display.
+    pub fn finished(&self) -> bool {
+        self.offset >= self.width() + DISPLAY_WIDTH
+    }
+
+    /// Moves the text one column to the left.
+    pub fn advance(&mut self) {
+        if !self.finished() {
+            self.offset += 1;
+        }
+    }
+
+    /// What the display shows right now, with lit columns at `brightness`.
+    pub fn frame(&self, brightness: u8) -> Screen {
+        let mut screen = Screen::new();
+
+        for col in 0..DISPLAY_WIDTH {
+            // The text starts just past the right edge of the display.
+            let Some(column) = (self.offset + col).checked_sub(DISPLAY_WIDTH) else { continue };
+            let Some((glyph, glyph_col)) = self.glyph_at(column) else { continue };
+
+            for row in 0..DISPLAY_HEIGHT {
+                if glyph.lit(row, glyph_col) {
+                    screen[(row, col)] = brightness;
+                }
+            }
+        }
+
+        screen
+    }
+
+    /// The glyph under `column` of the text and which of its columns it is.
+    ///  Gaps between glyphs count as part of the glyph before them.
+    fn glyph_at(&self, mut column: usize) -> Option<(Glyph, usize)> {
+        for c in self.chars() {
+            let glyph = glyph(c);
+            if column <= glyph.width {
+                return Some((glyph, column));
+            }
+            column -= glyph.width + 1;
+        }
+
+        None
+    }
+}
+
+impl Write for Scroll {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if self.len == Self::MAX_LEN {
+                return Err(fmt::Error);
+            }
+            self.text[self.len] = c;
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(mut scroll: Scroll) -> Vec<Screen> {
+        let mut frames = Vec::new();
+        while !scroll.finished() {
+            frames.push(scroll.frame(9));
+            scroll.advance();
+        }
+
+        frames
+    }
+
+    #[test]
+    fn text_comes_in_from_the_right() {
+        let frames = frames(Scroll::new("1"));
+
+        assert_eq!(frames.len(), 4 + DISPLAY_WIDTH);
+        assert_eq!(frames[0], Screen::new());
+        assert_eq!(frames[5].into_rows(), [
+            [0, 9, 0, 0, 0],
+            [9, 9, 0, 0, 0],
+            [0, 9, 0, 0, 0],
+            [0, 9, 0, 0, 0],
+            [9, 9, 9, 0, 0],
+        ]);
+        assert_eq!(frames[7].into_rows()[4], [9, 0, 0, 0, 0]);
+        assert_eq!(frames[8], Screen::new());
+    }
+
+    #[test]
+    fn characters_are_spaced_apart() {
+        let scroll = Scroll::new("11");
+        let frames = frames(scroll);
+
+        assert_eq!(scroll.width(), 8);
+        // Two columns of the first one, the gap, and two of the second.
+        assert_eq!(frames[6].into_rows()[4], [9, 9, 0, 9, 9]);
+    }
+
+    #[test]
+    fn formats_numbers() {
+        let scroll = Scroll::new(format_args!("Gen {}", 1234));
+
+        assert_eq!(scroll.chars().collect::<String>(), "Gen 1234");
+    }
+
+    #[test]
+    fn long_text_is_cut_short() {
+        let scroll = Scroll::new("A".repeat(40));
+
+        assert_eq!(scroll.chars().count(), Scroll::MAX_LEN);
+    }
+}
@@ -0,0 +1,734 @@
+//! Everything the board does with the world short of driving the hardware:
+//!  stepping it, seeding it, noticing when it settles, keeping it and the
+//!  settings in flash, and carrying out what the state machine asks for.
+//!
+//! The firmware and the simulator only supply the LEDs and the ticks, through
+//!  [`Hardware`], and somewhere to keep things, through [`Flash`], so that
+//!  both run the same simulation.
+
+use core::mem;
+
+use microbit::display::nonblocking::MAX_BRIGHTNESS;
+
+use crate::{
+    app::{App, Effects},
+    automaton::Automaton,
+    display::{icon, readout, shade, Screen, DISPLAY_HEIGHT, DISPLAY_WIDTH},
+    editor::Cursor,
+    engine::random_automata,
+    generations::{self, Generations},
+    history::{Fate, History},
+    library::Entry,
+    neighborhood::Neighborhood,
+    options::{Setting, Speed},
+    random::XorShift32,
+    rle::{self, Header, ParseRleError},
+    scroll::Scroll,
+    soup::Soup,
+    stats::{Statistic, Stats},
+    storage::{Flash, Settings, Slot, Storage},
+    viewport::Viewport,
+    wireworld,
+    world::World,
+};
+
+
+/// What the ticks come at while the world isn't running, to scroll text and
+///  flash the cursor at a steady pace whatever the speed.
+pub const TICK_PRESCALER: u32 = Speed::Normal.prescaler();
+
+
+/// What the simulation needs from the board it runs on.
+pub trait Hardware {
+    /// Lights the LEDs as `screen` says.
+    fn show(&mut self, screen: &Screen);
+
+    /// Makes [`Simulation::tick`] get called [`Speed::CLOCK_HZ`] /
+    ///  (`prescaler` + 1) times a second, or stops the ticks for `None`.
+    fn set_ticks(&mut self, prescaler: Option<u32>);
+
+    /// Hears about something worth logging, which is ignored by default.
+    fn report(&mut self, _report: Report) {}
+}
+
+/// Something that happened to the simulation, for the log.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub enum Report {
+    Started(Automaton),
+    /// A demo pattern was placed, to run under this automaton.
+    Placed(&'static str, Automaton),
+    Speed(Speed),
+    /// The world settled, from this generation on.
+    Settled(Fate, u32),
+    Saved(Slot),
+    Loaded(Slot),
+    /// A slot was loaded with nothing kept in it.
+    Empty(Slot),
+}
+
+
+/// The world and everything it's run with, driven by the state machine
+///  through [`Effects`] and by the ticks through [`Simulation::tick`].
+pub struct Simulation<C, F: Flash, H> {
+    world: C,
+    /// The part of the world the display shows.
+    viewport: Viewport,
+    settings: Settings,
+    neighborhood: Neighborhood,
+    /// What the menu chooses between, the first of which runs patterns that
+    ///  need a Generations rule of their own.
+    modes: &'static [Automaton],
+
+    /// The last few generations, to notice when the world stops changing.
+    history: History<16>,
+    /// How many generations ago the world died out or started repeating.
+    settled: Option<u32>,
+    reseed_due: bool,
+    stats: Stats,
+
+    /// Whether the world is stepped on the ticks, which also keep going while
+    ///  text is scrolling or the cursor is flashing.
+    running: bool,
+    /// Text scrolling across the display in place of the world, and what to
+    ///  show once it's gone if the world isn't running.
+    scroll: Option<(Scroll, Screen)>,
+    /// Where the cell being edited is, while editing.
+    cursor: Option<Cursor>,
+    /// Counts ticks, to time the cursor's flashing.
+    blink: u8,
+    /// Counts ticks while running, to step the world at the speed.
+    ticks: u32,
+
+    random: XorShift32,
+    /// How many worlds there have been since the automaton was chosen.
+    seeds: usize,
+    storage: Storage<F>,
+    hardware: H,
+}
+
+impl<C: World + Copy, F: Flash, H: Hardware> Simulation<C, F, H> {
+    /// A simulation with the settings kept in `storage`, on a blank world
+    ///  until the state machine starts.
+    pub fn new(modes: &'static [Automaton], storage: Storage<F>, hardware: H, random: XorShift32) -> Self {
+        let world = C::blank();
+        let mut simulation = Simulation {
+            world,
+            viewport: Viewport::new(14, 14),
+            settings: storage.settings(),
+            neighborhood: Neighborhood::Moore,
+            modes,
+            history: History::new(),
+            settled: None,
+            reseed_due: false,
+            stats: Stats::new(&world),
+            running: false,
+            scroll: None,
+            cursor: None,
+            blink: 0,
+            ticks: 0,
+            random,
+            seeds: 0,
+            storage,
+            hardware,
+        };
+        simulation.select(simulation.settings.automaton);
+
+        simulation
+    }
+
+    /// The state machine to run this with: paused on the world kept from
+    ///  before a reset, or in the menu if there isn't one.
+    pub fn pick_up(&mut self) -> App {
+        match self.storage.load(Slot::Last) {
+            Some(world) => {
+                self.restart(world);
+                App::resumed(self.modes)
+            },
+            None => App::new(self.modes),
+        }
+    }
+
+    pub const fn world(&self) -> &C {
+        &self.world
+    }
+
+    pub const fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub const fn settings(&self) -> Settings {
+        self.settings
+    }
+
+    pub const fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub const fn hardware(&self) -> &H {
+        &self.hardware
+    }
+
+    /// Moves the time on by a tick: steps the world if it's running and it's
+    ///  time to, then moves any text along or redraws the world. Returns
+    ///  whether the world was stepped.
+    pub fn tick(&mut self) -> bool {
+        let stepping = self.running && self.settings.speed.steps_on(self.ticks);
+        if self.running {
+            self.ticks = self.ticks.wrapping_add(1);
+        }
+
+        if stepping {
+            self.advance();
+            self.watch();
+        }
+
+        // The cursor flashes, spending four ticks on and then four off.
+        self.blink = self.blink.wrapping_add(1);
+        let cursor = self.cursor.filter(|_| self.blink & 4 == 0);
+
+        match self.scroll.as_mut() {
+            Some((text, backdrop)) => {
+                let frame = text.frame(MAX_BRIGHTNESS);
+                text.advance();
+                let finished = text.finished().then_some(*backdrop);
+
+                self.hardware.show(&frame);
+                if let Some(backdrop) = finished {
+                    self.scroll = None;
+                    if !self.running {
+                        self.hardware.show(&backdrop);
+                    }
+                    self.retick();
+                }
+            },
+            None => self.draw(cursor),
+        }
+
+        stepping
+    }
+
+    /// Whether a settled soup has been on show long enough to replace, which
+    ///  is only said once.
+    pub fn reseed_due(&mut self) -> bool {
+        mem::take(&mut self.reseed_due)
+    }
+
+    /// Switches to `automaton`. Another rule of the same kind carries on with
+    ///  the same world, and any other starts afresh.
+    pub fn set_rule(&mut self, automaton: Automaton) {
+        if mem::discriminant(&automaton) == mem::discriminant(&self.settings.automaton) {
+            self.select(automaton);
+            self.save_settings();
+            self.restart(self.world);
+        } else {
+            self.start(automaton);
+        }
+    }
+
+    /// Changes how fast the world runs, taking effect from the next tick.
+    pub fn set_speed(&mut self, speed: Speed) {
+        self.settings.speed = speed;
+        self.hardware.report(Report::Speed(speed));
+        self.retick();
+        self.save_settings();
+    }
+
+    /// Replaces the world with a fresh one from `seed`, the same every time.
+    pub fn reseed_from(&mut self, seed: u32) {
+        self.random = XorShift32::new(seed);
+        self.reseed();
+    }
+
+    /// Replaces the world with the RLE pattern in `text`, with its top left
+    ///  at the viewport's, and switches to its rule as [`Effects::place`] does
+    ///  for the demo patterns.
+    pub fn load_rle(&mut self, text: &str) -> Result<Header, ParseRleError> {
+        let mut world = C::blank();
+        let header = rle::read(text, &mut world, self.viewport.row, self.viewport.col)?;
+
+        self.select(self.pattern_rule(header.rule));
+        self.save_settings();
+        self.restart(world);
+
+        Ok(header)
+    }
+
+    /// Keeps the settings as they are now, for after a reset.
+    fn save_settings(&mut self) {
+        self.storage.save_settings(&self.settings);
+    }
+
+    /// Starts or stops the ticks, which are needed to step the world, scroll
+    ///  text and blink the cursor, and sets how fast they come. They follow
+    ///  the speed while running and go at the usual pace otherwise.
+    fn retick(&mut self) {
+        let ticking = self.running || self.scroll.is_some() || self.cursor.is_some();
+        let prescaler = if self.running { self.settings.speed.prescaler() } else { TICK_PRESCALER };
+
+        self.hardware.set_ticks(ticking.then_some(prescaler));
+    }
+
+    /// Switches to running `automaton`. Elementary rules grow along the bottom
+    ///  of the world, so the viewport moves down to follow them.
+    fn select(&mut self, automaton: Automaton) {
+        self.settings.automaton = automaton;
+
+        if let Automaton::Elementary(_) = automaton {
+            self.viewport = Viewport::new(C::HEIGHT - DISPLAY_HEIGHT, self.viewport.col);
+        }
+    }
+
+    /// Which of the modes is the same kind of automaton as `automaton`.
+    fn mode_index(&self, automaton: &Automaton) -> usize {
+        self.modes.iter().position(|mode| mem::discriminant(mode) == mem::discriminant(automaton)).unwrap_or(0)
+    }
+
+    /// What a pattern with `rule` runs under: its own rule, or the current one
+    ///  if that's a Generations one, or the first mode if not.
+    fn pattern_rule(&self, rule: Option<Generations>) -> Automaton {
+        match (rule, self.settings.automaton) {
+            (Some(rule), _) => Automaton::Generations(rule),
+            (None, current @ Automaton::Generations(_)) => current,
+            (None, _) => self.modes[0],
+        }
+    }
+
+    /// A fresh world for the automaton, the `seeds`th since it was chosen: a
+    ///  soup centred in the viewport, or for Wireworld one of the bundled
+    ///  circuits, placed under the viewport so that it's in view. Elementary
+    ///  rules start from a single cell and then from random rows.
+    fn seed(&mut self) -> C {
+        let viewport = self.viewport;
+
+        match self.settings.automaton {
+            Automaton::Generations(_) => self.settings.soup.generate(
+                &mut self.random,
+                viewport.row + DISPLAY_HEIGHT / 2,
+                viewport.col + DISPLAY_WIDTH / 2,
+            ),
+
+            Automaton::Wireworld => {
+                let mut world = C::blank();
+                wireworld::CIRCUITS[self.seeds % wireworld::CIRCUITS.len()].place(&mut world, viewport.row, viewport.col);
+
+                world
+            },
+
+            Automaton::Elementary(_) => {
+                let newest = C::HEIGHT - 1;
+                let mut world = C::blank();
+
+                if self.seeds == 0 {
+                    world.set(newest, (viewport.col + DISPLAY_WIDTH / 2) % C::WIDTH, 1);
+                } else {
+                    let soup: C = random_automata(&mut self.random);
+                    for col in 0..C::WIDTH {
+                        world.set(newest, col, soup.get(newest, col));
+                    }
+                }
+
+                world
+            },
+        }
+    }
+
+    /// Replaces the world with a fresh one, which has no history yet.
+    fn restart(&mut self, world: C) {
+        self.world = world;
+        self.history.clear();
+        self.stats = Stats::new(&world);
+        self.settled = None;
+        self.reseed_due = false;
+
+        self.show_world();
+    }
+
+    /// Moves the world on a generation, counting the changes towards the
+    ///  statistics.
+    fn advance(&mut self) {
+        let previous = self.world;
+        self.world = self.settings.automaton.step(&previous, self.settings.boundary, self.neighborhood);
+        self.stats.record(&previous, &self.world);
+    }
+
+    /// Keeps track of whether the world has settled, and once it's been
+    ///  settled for long enough, asks for a new soup. Only soups are replaced,
+    ///  since Wireworld's circuits are meant to repeat.
+    fn watch(&mut self) {
+        let Some(fate) = self.history.record(&self.world) else {
+            self.settled = None;
+            return;
+        };
+
+        let generations = match self.settled {
+            Some(generations) => generations + 1,
+            None => {
+                self.stats.settle(fate);
+                let from = self.stats.settled_at.unwrap_or(self.stats.generation);
+                self.hardware.report(Report::Settled(fate, from));
+                0
+            },
+        };
+        self.settled = Some(generations);
+
+        if self.settings.reseed.is_due(generations) && matches!(self.settings.automaton, Automaton::Generations(_)) {
+            self.reseed_due = true;
+        }
+    }
+
+    /// What the display shows of `world` under the viewport, without the
+    ///  cursor.
+    fn screen(&self, world: &C) -> Screen {
+        let automaton = self.settings.automaton;
+        self.viewport.view(world).map(|cell| shade(&automaton, cell))
+    }
+
+    fn draw(&mut self, cursor: Option<Cursor>) {
+        let mut screen = self.screen(&self.world);
+        if let Some(cursor) = cursor {
+            cursor.mark(&mut screen);
+        }
+
+        self.hardware.show(&screen);
+    }
+
+    /// Scrolls `text` across the display, one column every tick, without
+    ///  stopping the world. When paused, `backdrop` is shown once the text has
+    ///  gone.
+    fn show_text(&mut self, text: Scroll, backdrop: Screen) {
+        self.scroll = Some((text, backdrop));
+        self.retick();
+    }
+
+    /// Drops any text still scrolling, as something else is about to be shown.
+    fn stop_scrolling(&mut self) {
+        if self.scroll.take().is_some() {
+            self.retick();
+        }
+    }
+}
+
+impl<C: World + Copy, F: Flash, H: Hardware> Effects for Simulation<C, F, H> {
+    fn run(&mut self, running: bool) {
+        self.running = running;
+        self.retick();
+    }
+
+    fn show_world(&mut self) {
+        self.stop_scrolling();
+        self.draw(self.cursor);
+    }
+
+    fn show_icon(&mut self, automaton: &Automaton) {
+        self.stop_scrolling();
+        self.hardware.show(&icon(automaton));
+    }
+
+    /// Scrolls the name and value of `statistic`, then leaves it up in binary,
+    ///  numbered by where it is in [`Statistic::ALL`] from one.
+    fn show_statistic(&mut self, statistic: Statistic) {
+        let value = statistic.of(&self.stats);
+        let text = match (statistic, self.stats.settled_at) {
+            (Statistic::SettledAt, None) => Scroll::new("Not settled"),
+            _ => Scroll::new(format_args!("{} {}", statistic.name(), value)),
+        };
+
+        self.show_text(text, readout(statistic.index() as u8 + 1, value));
+    }
+
+    fn show_setting(&mut self, setting: Setting) {
+        self.show_text(Scroll::new(setting.name()), readout(setting.index() as u8 + 1, 0));
+    }
+
+    /// Scrolls what `setting` is set to, then leaves it up in binary as a
+    ///  number: the mode or preset rule counting from one, the elementary rule
+    ///  number, the generations a second, the soup density or the generations
+    ///  a settled soup stays.
+    fn show_value(&mut self, setting: Setting) {
+        let Settings { automaton, speed, boundary, soup, reseed } = self.settings;
+
+        let value = match setting {
+            Setting::Mode => self.mode_index(&automaton) as u32 + 1,
+            Setting::Rule => match automaton {
+                Automaton::Generations(rule) => {
+                    generations::PRESETS.iter().position(|(_, preset)| *preset == rule).map_or(0, |at| at as u32 + 1)
+                },
+                Automaton::Wireworld => 1,
+                Automaton::Elementary(rule) => rule.rule as u32,
+            },
+            Setting::Speed => speed.generations_per_second(),
+            Setting::Boundary => boundary as u32 + 1,
+            Setting::Density => soup.density as u32,
+            Setting::Reseed => reseed.generations().unwrap_or(0),
+        };
+
+        let text = match setting {
+            Setting::Mode => Scroll::new(match automaton {
+                Automaton::Generations(_) => "Life-like",
+                Automaton::Wireworld => "Wireworld",
+                Automaton::Elementary(_) => "Elementary",
+            }),
+            Setting::Rule => Scroll::new(automaton),
+            Setting::Speed => Scroll::new(speed),
+            Setting::Boundary => Scroll::new(boundary),
+            Setting::Density => Scroll::new(format_args!("{}%", value)),
+            Setting::Reseed => Scroll::new(reseed),
+        };
+
+        self.show_text(text, readout(setting.index() as u8 + 1, value));
+    }
+
+    fn set_cursor(&mut self, cursor: Option<Cursor>) {
+        self.cursor = cursor;
+        self.retick();
+        self.show_world();
+    }
+
+    fn start(&mut self, automaton: Automaton) {
+        self.hardware.report(Report::Started(automaton));
+        self.select(automaton);
+        self.seeds = 0;
+        let world = self.seed();
+        self.restart(world);
+        self.save_settings();
+    }
+
+    fn reseed(&mut self) {
+        self.seeds += 1;
+        let world = self.seed();
+        self.restart(world);
+    }
+
+    fn step(&mut self) {
+        self.advance();
+        self.show_world();
+    }
+
+    fn pan(&mut self, rows: isize, cols: isize) {
+        self.viewport.pan::<C>(rows, cols);
+        self.show_world();
+    }
+
+    fn toggle(&mut self, cursor: Cursor) {
+        let mut world = self.world;
+        cursor.toggle(&mut world, self.viewport, &self.settings.automaton);
+        self.restart(world);
+    }
+
+    fn change_soup(&mut self, change: fn(Soup) -> Soup) {
+        self.settings.soup = change(self.settings.soup);
+        self.save_settings();
+    }
+
+    fn change_speed(&mut self, change: fn(Speed) -> Speed) {
+        self.set_speed(change(self.settings.speed));
+    }
+
+    /// Changing the mode or rule starts it afresh, since the cells of one
+    ///  automaton can mean nothing to another.
+    fn change(&mut self, setting: Setting) {
+        let settings = self.settings;
+        match setting {
+            Setting::Mode => self.start(self.modes[(self.mode_index(&settings.automaton) + 1) % self.modes.len()]),
+            Setting::Rule => self.start(settings.automaton.next_rule()),
+            Setting::Speed => self.set_speed(settings.speed.next()),
+            Setting::Boundary => self.settings.boundary = settings.boundary.next(),
+            Setting::Density => self.settings.soup = settings.soup.denser(),
+            Setting::Reseed => self.settings.reseed = settings.reseed.next(),
+        }
+        self.save_settings();
+    }
+
+    /// Scrolls the slot's number, then shows the part of the world kept in it
+    ///  under the viewport, or nothing if it's empty.
+    fn show_slot(&mut self, slot: usize) {
+        let backdrop = match self.storage.load::<C>(Slot::Saved(slot)) {
+            Some(world) => self.screen(&world),
+            None => Screen::new(),
+        };
+
+        self.show_text(Scroll::new(format_args!("Slot {}", slot + 1)), backdrop);
+    }
+
+    fn save(&mut self, slot: Slot) {
+        self.storage.save(slot, &self.world);
+        self.hardware.report(Report::Saved(slot));
+    }
+
+    fn load(&mut self, slot: Slot) {
+        match self.storage.load(slot) {
+            Some(world) => {
+                self.restart(world);
+                self.hardware.report(Report::Loaded(slot));
+            },
+            None => self.hardware.report(Report::Empty(slot)),
+        }
+    }
+
+    /// Patterns without a rule of their own are run with the current rule if
+    ///  it's a Generations one, or with the first mode if not.
+    fn place(&mut self, pattern: &'static Entry) {
+        let automaton = self.pattern_rule(pattern.rule);
+        self.select(automaton);
+        self.save_settings();
+        self.hardware.report(Report::Placed(pattern.name, automaton));
+
+        let viewport = self.viewport;
+        let mut world = C::blank();
+        pattern.place_centred(&mut world, viewport.row + DISPLAY_HEIGHT / 2, viewport.col + DISPLAY_WIDTH / 2);
+        self.restart(world);
+
+        self.show_text(Scroll::new(pattern.name), self.screen(&world));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elementary::RULE_30,
+        grid::Grid,
+        history::Reseed,
+        library::PATTERNS,
+        rule,
+        storage::memory::MemoryFlash,
+    };
+
+    const MODES: [Automaton; 3] = [
+        Automaton::Generations(Generations::from_rule(rule::LIFE)),
+        Automaton::Wireworld,
+        Automaton::Elementary(RULE_30),
+    ];
+
+    const BLOCK: &str = "x = 2, y = 2\n2o$2o!";
+
+    /// Remembers what was last shown and how the ticks were set, and writes
+    ///  down what was reported.
+    #[derive(Default)]
+    struct Board {
+        screen: Screen,
+        ticks: Option<u32>,
+        reports: Vec<Report>,
+    }
+
+    impl Hardware for Board {
+        fn show(&mut self, screen: &Screen) {
+            self.screen = *screen;
+        }
+
+        fn set_ticks(&mut self, prescaler: Option<u32>) {
+            self.ticks = prescaler;
+        }
+
+        fn report(&mut self, report: Report) {
+            self.reports.push(report);
+        }
+    }
+
+    fn simulation() -> Simulation<Grid<32, 32>, MemoryFlash, Board> {
+        Simulation::new(&MODES, Storage::new(MemoryFlash::new()), Board::default(), XorShift32::default())
+    }
+
+    /// Runs the world for up to `ticks` ticks, and says on which of them a new
+    ///  soup first came due.
+    fn first_reseed(simulation: &mut Simulation<Grid<32, 32>, MemoryFlash, Board>, ticks: u32) -> Option<u32> {
+        simulation.run(true);
+        (0..ticks).find(|_| simulation.tick() && simulation.reseed_due())
+    }
+
+    #[test]
+    fn starts_with_the_kept_settings_and_in_the_menu() {
+        let mut simulation = simulation();
+
+        assert_eq!(simulation.settings(), Settings::default());
+        assert_eq!(simulation.pick_up().mode(), crate::app::Mode::Menu(0));
+    }
+
+    #[test]
+    fn ticks_only_come_when_needed() {
+        let mut simulation = simulation();
+        assert_eq!(simulation.hardware().ticks, None);
+
+        simulation.run(true);
+        assert_eq!(simulation.hardware().ticks, Some(Speed::Normal.prescaler()));
+        simulation.change_speed(Speed::faster);
+        assert_eq!(simulation.hardware().ticks, Some(Speed::Fast.prescaler()));
+
+        simulation.run(false);
+        assert_eq!(simulation.hardware().ticks, None);
+
+        // Text scrolls at the usual pace, and the ticks stop once it's gone.
+        simulation.show_setting(Setting::Mode);
+        assert_eq!(simulation.hardware().ticks, Some(TICK_PRESCALER));
+        while simulation.hardware().ticks.is_some() {
+            simulation.tick();
+        }
+        assert_eq!(simulation.hardware().screen, readout(1, 0));
+    }
+
+    #[test]
+    fn settled_soups_are_replaced_unless_reseeding_is_off() {
+        let mut simulation = simulation();
+        simulation.load_rle(BLOCK).unwrap();
+
+        // The block is a still life from the start, which the history notices
+        //  a generation later.
+        assert_eq!(first_reseed(&mut simulation, 100), Some(31));
+        assert!(matches!(simulation.hardware().reports[..], [Report::Settled(Fate::StillLife, _)]));
+
+        simulation.change(Setting::Reseed);
+        simulation.change(Setting::Reseed);
+        assert_eq!(simulation.settings().reseed, Reseed::Never);
+        simulation.load_rle(BLOCK).unwrap();
+        assert_eq!(first_reseed(&mut simulation, 200), None);
+    }
+
+    #[test]
+    fn circuits_are_left_to_repeat() {
+        let mut simulation = simulation();
+        simulation.start(Automaton::Wireworld);
+
+        assert_eq!(first_reseed(&mut simulation, 200), None);
+    }
+
+    #[test]
+    fn rules_of_the_same_kind_carry_on_with_the_world() {
+        let mut simulation = simulation();
+        simulation.load_rle(BLOCK).unwrap();
+        let block = *simulation.world();
+
+        simulation.set_rule(Automaton::Generations(Generations::from_rule(rule::HIGHLIFE)));
+        assert_eq!(*simulation.world(), block);
+
+        simulation.set_rule(Automaton::Elementary(RULE_30));
+        assert_ne!(*simulation.world(), block);
+        assert_eq!(simulation.viewport().row, 32 - DISPLAY_HEIGHT);
+        assert_eq!(simulation.hardware().reports.last(), Some(&Report::Started(Automaton::Elementary(RULE_30))));
+    }
+
+    #[test]
+    fn patterns_without_a_rule_need_a_generations_one() {
+        let pulsar = PATTERNS.iter().find(|entry| entry.name == "Pulsar").unwrap();
+        let mut simulation = simulation();
+        simulation.start(Automaton::Wireworld);
+
+        simulation.place(pulsar);
+        assert_eq!(simulation.settings().automaton, MODES[0]);
+        assert_eq!(simulation.hardware().reports.last(), Some(&Report::Placed("Pulsar", MODES[0])));
+    }
+
+    #[test]
+    fn worlds_are_kept_and_loaded() {
+        let mut simulation = simulation();
+        simulation.load_rle(BLOCK).unwrap();
+        simulation.save(Slot::Saved(2));
+        simulation.reseed();
+
+        simulation.load(Slot::Saved(1));
+        assert_eq!(simulation.hardware().reports.last(), Some(&Report::Empty(Slot::Saved(1))));
+
+        simulation.load(Slot::Saved(2));
+        assert_eq!(simulation.stats().population, 4);
+    }
+}
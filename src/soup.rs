@@ -0,0 +1,248 @@
+//! Random starting worlds, with some control over how they're laid out.
+
+use crate::{random::Random, world::World};
+
+
+/// How the live cells of a soup are laid out.
+///
+/// The symmetric patterns are named after their symmetry groups: `C2` looks
+///  the same turned half way round, `C4` turned a quarter of the way, and `D8`
+///  also when mirrored. Rotating a non-square world a quarter turn doesn't fit
+///  it back on itself, so `C4` and `D8` only fill the largest square in the
+///  middle.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    /// Every cell decided on its own.
+    #[default]
+    Uniform,
+    C2,
+    C4,
+    D8,
+    /// A disc of [`Soup::BLOB_RADIUS`] around the centre, with nothing else.
+    Blob,
+}
+
+impl Pattern {
+    /// The pattern after this one, for cycling through them all.
+    pub const fn next(self) -> Pattern {
+        match self {
+            Pattern::Uniform => Pattern::C2,
+            Pattern::C2 => Pattern::C4,
+            Pattern::C4 => Pattern::D8,
+            Pattern::D8 => Pattern::Blob,
+            Pattern::Blob => Pattern::Uniform,
+        }
+    }
+}
+
+
+/// A recipe for random worlds: a `pattern` where each cell it covers is alive
+///  with a chance of `density` percent.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub struct Soup {
+    pub pattern: Pattern,
+    pub density: u8,
+}
+
+impl Soup {
+    pub const MIN_DENSITY: u8 = 10;
+    pub const MAX_DENSITY: u8 = 90;
+    pub const DENSITY_STEP: u8 = 10;
+
+    pub const BLOB_RADIUS: usize = 3;
+
+    pub const fn new(pattern: Pattern, density: u8) -> Self {
+        Soup { pattern, density }
+    }
+
+    /// The next density up, going back to the lowest after the highest.
+    pub const fn denser(self) -> Self {
+        let density = if self.density >= Self::MAX_DENSITY {
+            Self::MIN_DENSITY
+        } else {
+            self.density + Self::DENSITY_STEP
+        };
+
+        Soup { density, ..self }
+    }
+
+    /// A fresh world with its pattern centred on `row`, `col`. Patterns that
+    ///  run past the edge of the world wrap around it.
+    pub fn generate<C: World, R: Random>(&self, random: &mut R, row: usize, col: usize) -> C {
+        let mut result = C::blank();
+
+        // The pattern is laid out with its centre in the middle of the world,
+        //  then moved over to `row`, `col`.
+        let shift_row = row + C::HEIGHT - C::HEIGHT / 2;
+        let shift_col = col + C::WIDTH - C::WIDTH / 2;
+
+        let mut orbit = [(0, 0); 8];
+        for local_row in 0..C::HEIGHT {
+            for local_col in 0..C::WIDTH {
+                let count = self.orbit::<C>(local_row, local_col, &mut orbit);
+
+                // Every cell of an orbit shares one coin flip, made when the
+                //  first of them comes up.
+                if count == 0 || orbit[..count].iter().any(|cell| *cell < (local_row, local_col)) {
+                    continue;
+                }
+
+                if chance(random, self.density) {
+                    for (orbit_row, orbit_col) in &orbit[..count] {
+                        result.set((orbit_row + shift_row) % C::HEIGHT, (orbit_col + shift_col) % C::WIDTH, 1);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The cells that must match `row`, `col` for the pattern to keep its
+    ///  symmetry, written to the front of `buffer`. None at all when the cell
+    ///  lies outside the pattern.
+    fn orbit<C: World>(&self, row: usize, col: usize, buffer: &mut [(usize, usize); 8]) -> usize {
+        let (width, height) = (C::WIDTH, C::HEIGHT);
+
+        let side = width.min(height);
+        let (top, left) = ((height - side) / 2, (width - side) / 2);
+        let in_square = (top..top + side).contains(&row) && (left..left + side).contains(&col);
+
+        let mut count = 0;
+        let mut push = |cell| {
+            if !buffer[..count].contains(&cell) {
+                buffer[count] = cell;
+                count += 1;
+            }
+        };
+
+        match self.pattern {
+            Pattern::Uniform => push((row, col)),
+
+            Pattern::C2 => {
+                push((row, col));
+                push((height - 1 - row, width - 1 - col));
+            },
+
+            Pattern::C4 | Pattern::D8 if in_square => {
+                let (r, c) = (row - top, col - left);
+                let last = side - 1;
+
+                // The four quarter turns, then the same again mirrored along
+                //  the diagonal.
+                let images = [
+                    (r, c), (c, last - r), (last - r, last - c), (last - c, r),
+                    (c, r), (last - r, c), (last - c, last - r), (r, last - c),
+                ];
+                let used = if self.pattern == Pattern::D8 { 8 } else { 4 };
+
+                for (r, c) in &images[..used] {
+                    push((r + top, c + left));
+                }
+            },
+
+            Pattern::C4 | Pattern::D8 => {},
+
+            Pattern::Blob => {
+                let row_offset = row.abs_diff(height / 2);
+                let col_offset = col.abs_diff(width / 2);
+
+                if row_offset * row_offset + col_offset * col_offset <= Self::BLOB_RADIUS * Self::BLOB_RADIUS {
+                    push((row, col));
+                }
+            },
+        }
+
+        count
+    }
+}
+
+impl Default for Soup {
+    /// An even mix of live and dead cells everywhere.
+    fn default() -> Self {
+        Soup::new(Pattern::Uniform, 50)
+    }
+}
+
+
+/// Whether a roll of `random` comes in under `percent`.
+fn chance<R: Random>(random: &mut R, percent: u8) -> bool {
+    (random.next_u32() as u64 * 100) >> 32 < percent as u64
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{grid::Grid, random::XorShift32};
+
+    type Big = Grid<32, 32>;
+
+    fn alive<const W: usize, const H: usize>(world: &Grid<W, H>) -> usize {
+        world.iter().filter(|cell| **cell != 0).count()
+    }
+
+    #[test]
+    fn density_sets_the_share_of_live_cells() {
+        let mut random = XorShift32::default();
+
+        for density in [10, 50, 90] {
+            let world: Big = Soup::new(Pattern::Uniform, density).generate(&mut random, 16, 16);
+            let percent = alive(&world) * 100 / (Big::WIDTH * Big::HEIGHT);
+
+            assert!(percent.abs_diff(density as usize) <= 5, "{percent}% for {density}%");
+        }
+
+        assert_eq!(alive(&Soup::new(Pattern::Uniform, 0).generate::<Big, _>(&mut random, 0, 0)), 0);
+        assert_eq!(alive(&Soup::new(Pattern::Uniform, 100).generate::<Big, _>(&mut random, 0, 0)), 32 * 32);
+    }
+
+    #[test]
+    fn denser_cycles_through_the_densities() {
+        let densities = core::iter::successors(Some(Soup::default()), |soup| Some(soup.denser()))
+            .map(|soup| soup.density)
+            .take(10)
+            .collect::<Vec<_>>();
+
+        assert_eq!(densities, [50, 60, 70, 80, 90, 10, 20, 30, 40, 50]);
+    }
+
+    #[test]
+    fn symmetric_soups_keep_their_symmetry() {
+        let mut random = XorShift32::new(99);
+
+        let c2: Grid<7, 4> = Soup::new(Pattern::C2, 50).generate(&mut random, 2, 3);
+        for (row, col) in (0..4).flat_map(|row| (0..7).map(move |col| (row, col))) {
+            assert_eq!(c2[(row, col)], c2[(3 - row, 6 - col)]);
+        }
+
+        let c4: Big = Soup::new(Pattern::C4, 50).generate(&mut random, 16, 16);
+        let d8: Big = Soup::new(Pattern::D8, 50).generate(&mut random, 16, 16);
+        for (row, col) in (0..32).flat_map(|row| (0..32).map(move |col| (row, col))) {
+            assert_eq!(c4[(row, col)], c4[(col, 31 - row)]);
+            assert_eq!(d8[(row, col)], d8[(col, 31 - row)]);
+            assert_eq!(d8[(row, col)], d8[(col, row)]);
+        }
+        assert_ne!(alive(&c4), 0);
+    }
+
+    #[test]
+    fn c4_leaves_the_sides_of_wide_worlds_empty() {
+        let world: Grid<8, 4> = Soup::new(Pattern::C4, 100).generate(&mut XorShift32::default(), 2, 4);
+
+        assert_eq!(world.rows()[0], [0, 0, 1, 1, 1, 1, 0, 0]);
+        assert_eq!(alive(&world), 16);
+    }
+
+    #[test]
+    fn blob_stays_within_its_radius_of_the_centre() {
+        let world: Big = Soup::new(Pattern::Blob, 100).generate(&mut XorShift32::default(), 2, 30);
+
+        // Centred next to the corner, so it wraps around onto the far sides.
+        assert_eq!(world[(2, 30)], 1);
+        assert_eq!(world[(2, 1)], 1);
+        assert_eq!(world[(31, 30)], 1);
+        assert_eq!(world[(2, 2)], 0);
+        assert_eq!(alive(&world), 29);
+    }
+}
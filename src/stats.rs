@@ -0,0 +1,253 @@
+//! Running totals on how a world has been getting on since it started.
+
+use crate::{history::Fate, world::World};
+
+
+/// Counts of the live cells of a world, generation by generation.
+///
+/// A cell counts as alive in state `1`, which for Wireworld means an electron's
+///  head. Cells of Generations rules stop counting once they start dying.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// How many ticks the world has been stepped since it started.
+    pub generation: u32,
+    pub population: u32,
+    pub min_population: u32,
+    pub max_population: u32,
+    /// Cells that came alive in the last tick.
+    pub births: u32,
+    /// Cells that stopped being alive in the last tick.
+    pub deaths: u32,
+    /// The generation from which the world has been repeating itself, once
+    ///  that's been noticed.
+    pub settled_at: Option<u32>,
+}
+
+impl Stats {
+    /// Stats for a world that's just been started as `world`.
+    pub fn new<C: World>(world: &C) -> Self {
+        let population = population(world);
+
+        Stats {
+            generation: 0,
+            population,
+            min_population: population,
+            max_population: population,
+            births: 0,
+            deaths: 0,
+            settled_at: None,
+        }
+    }
+
+    /// Counts the changes from `previous` to `next`, one generation on.
+    pub fn record<C: World>(&mut self, previous: &C, next: &C) {
+        self.births = 0;
+        self.deaths = 0;
+
+        for row in 0..C::HEIGHT {
+            for col in 0..C::WIDTH {
+                match (previous.get(row, col) == 1, next.get(row, col) == 1) {
+                    (false, true) => self.births += 1,
+                    (true, false) => self.deaths += 1,
+                    _ => {},
+                }
+            }
+        }
+
+        self.generation += 1;
+        self.population = self.population + self.births - self.deaths;
+        self.min_population = self.min_population.min(self.population);
+        self.max_population = self.max_population.max(self.population);
+    }
+
+    /// Notes that the current generation turned out to be a repeat with `fate`,
+    ///  so the world has been settled since one period ago. Only the first
+    ///  time counts.
+    pub fn settle(&mut self, fate: Fate) {
+        if self.settled_at.is_none() {
+            self.settled_at = Some(self.generation.saturating_sub(fate.period() as u32));
+        }
+    }
+}
+
+
+/// One of the numbers in [`Stats`], for showing them one at a time.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq, Hash)]
+pub enum Statistic {
+    #[default]
+    Generation,
+    Population,
+    MinPopulation,
+    MaxPopulation,
+    Births,
+    Deaths,
+    SettledAt,
+}
+
+impl Statistic {
+    pub const ALL: [Statistic; 7] = [
+        Statistic::Generation,
+        Statistic::Population,
+        Statistic::MinPopulation,
+        Statistic::MaxPopulation,
+        Statistic::Births,
+        Statistic::Deaths,
+        Statistic::SettledAt,
+    ];
+
+    /// Where this is in [`Statistic::ALL`].
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// A short name, to show alongside the value.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Statistic::Generation => "Gen",
+            Statistic::Population => "Pop",
+            Statistic::MinPopulation => "Min",
+            Statistic::MaxPopulation => "Max",
+            Statistic::Births => "Births",
+            Statistic::Deaths => "Deaths",
+            Statistic::SettledAt => "Settled",
+        }
+    }
+
+    /// The statistic after this one, for cycling through them all.
+    pub const fn next(self) -> Statistic {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The value of this statistic in `stats`. A world that hasn't settled yet
+    ///  reads zero.
+    pub const fn of(self, stats: &Stats) -> u32 {
+        match self {
+            Statistic::Generation => stats.generation,
+            Statistic::Population => stats.population,
+            Statistic::MinPopulation => stats.min_population,
+            Statistic::MaxPopulation => stats.max_population,
+            Statistic::Births => stats.births,
+            Statistic::Deaths => stats.deaths,
+            Statistic::SettledAt => match stats.settled_at {
+                Some(generation) => generation,
+                None => 0,
+            },
+        }
+    }
+}
+
+
+/// How many cells of `world` are alive.
+pub fn population<C: World>(world: &C) -> u32 {
+    let mut count = 0;
+    for row in 0..C::HEIGHT {
+        for col in 0..C::WIDTH {
+            count += (world.get(row, col) == 1) as u32;
+        }
+    }
+
+    count
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        boundary::Boundary, engine::update_automata, generations::BRIANS_BRAIN, grid::Grid, history::History,
+        neighborhood::Neighborhood, rule,
+    };
+
+    const BLINKER: Grid<5, 5> = Grid::from_rows([
+        [0, 0, 0, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 1, 0, 0],
+        [0, 0, 0, 0, 0],
+    ]);
+
+    #[test]
+    fn counts_births_and_deaths() {
+        let mut stats = Stats::new(&BLINKER);
+        let next = update_automata(&BLINKER, Boundary::Dead, Neighborhood::Moore, rule::LIFE);
+        stats.record(&BLINKER, &next);
+
+        assert_eq!(stats.generation, 1);
+        assert_eq!((stats.births, stats.deaths), (2, 2));
+        assert_eq!(stats.population, 3);
+    }
+
+    #[test]
+    fn tracks_the_population_range() {
+        let glider = Grid::<8, 8>::from_rows([
+            [0, 1, 0, 0, 0, 0, 0, 0],
+            [0, 0, 1, 0, 0, 0, 0, 0],
+            [1, 1, 1, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0],
+        ]);
+
+        // The glider crashes into the dead edge and leaves a block behind.
+        let mut world = glider;
+        let mut stats = Stats::new(&world);
+        for _ in 0..30 {
+            let next = update_automata(&world, Boundary::Dead, Neighborhood::Moore, rule::LIFE);
+            stats.record(&world, &next);
+            world = next;
+        }
+
+        assert_eq!(stats.population, population(&world));
+        assert_eq!(stats.population, 4);
+        assert_eq!((stats.min_population, stats.max_population), (3, 5));
+    }
+
+    #[test]
+    fn dying_cells_arent_counted_alive() {
+        let world = Grid::<3, 1>::from_rows([[1, 2, 0]]);
+        let mut stats = Stats::new(&world);
+        let next = update_automata(&world, Boundary::Dead, Neighborhood::Moore, BRIANS_BRAIN);
+        stats.record(&world, &next);
+
+        assert_eq!(Stats::new(&world).population, 1);
+        assert_eq!(next.into_rows(), [[2, 0, 0]]);
+        assert_eq!((stats.births, stats.deaths, stats.population), (0, 1, 0));
+    }
+
+    #[test]
+    fn settles_where_the_cycle_began() {
+        let mut world = BLINKER;
+        let mut stats = Stats::new(&world);
+        let mut history = History::<4>::new();
+        history.record(&world);
+
+        while stats.settled_at.is_none() {
+            let next = update_automata(&world, Boundary::Dead, Neighborhood::Moore, rule::LIFE);
+            stats.record(&world, &next);
+            world = next;
+
+            if let Some(fate) = history.record(&world) {
+                stats.settle(fate);
+            }
+        }
+
+        assert_eq!(stats.generation, 2);
+        assert_eq!(stats.settled_at, Some(0));
+
+        stats.settle(Fate::StillLife);
+        assert_eq!(Statistic::SettledAt.of(&stats), 0);
+    }
+
+    #[test]
+    fn statistics_cycle_through_every_value() {
+        let stats = Stats { generation: 1, population: 2, min_population: 3, max_population: 4, births: 5, deaths: 6, settled_at: Some(7) };
+        let values = core::iter::successors(Some(Statistic::default()), |statistic| Some(statistic.next()))
+            .take(8)
+            .map(|statistic| statistic.of(&stats))
+            .collect::<Vec<_>>();
+
+        assert_eq!(values, [1, 2, 3, 4, 5, 6, 7, 1]);
+    }
+}
@@ -0,0 +1,544 @@
+//! Keeping settings and worlds in flash, so that they survive a reset.
+//!
+//! Each kind of record has its own log of at least two flash pages, which new
+//!  records are added to until it wraps back around. A page is only erased
+//!  when the log comes back round to it, by which point the newest record is
+//!  on another page, so that pages wear evenly and losing power part way
+//!  through never loses everything. Records carry a sequence number to find
+//!  the newest, and a CRC so that torn or corrupt ones are passed over.
+
+use crate::{
+    automaton::Automaton,
+    boundary::Boundary,
+    elementary::Elementary,
+    generations::Generations,
+    history::Reseed,
+    options::Speed,
+    rule::{self, Rule},
+    soup::{Pattern, Soup},
+    world::World,
+};
+
+
+/// Flash that's erased a page at a time, to all ones, and written a word at a
+///  time, which can only clear bits. Addresses are in bytes from the start of
+///  the storage and always word aligned.
+pub trait Flash {
+    /// How many bytes are erased at once.
+    const PAGE_SIZE: usize;
+
+    fn read(&self, address: usize) -> u32;
+
+    /// Sets every bit of the `page`th page from the start of the storage.
+    fn erase(&mut self, page: usize);
+
+    fn write(&mut self, address: usize, word: u32);
+
+    /// Called once a whole record is written, for flash that's only kept
+    ///  somewhere at times, and does nothing by default.
+    fn flush(&mut self) {}
+}
+
+/// How a word reads once it's been erased.
+pub const ERASED: u32 = u32::MAX;
+
+/// Marks the start of a record, to tell it apart from erased or stray words.
+const MAGIC: u32 = 0x4C49_4645;
+
+
+/// The CRC-32 used by zip and Ethernet, a bit at a time, as a table wouldn't
+///  be worth the flash.
+pub fn crc32(bytes: impl IntoIterator<Item = u8>) -> u32 {
+    let mut crc = !0;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+fn crc_words(words: impl IntoIterator<Item = u32>) -> u32 {
+    crc32(words.into_iter().flat_map(u32::to_le_bytes))
+}
+
+
+/// A log of records that are all `words` long, over `pages` pages from
+///  `first_page`.
+///
+/// A record is the magic word, its sequence number, the words themselves and
+///  a CRC of the sequence number and words.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Log {
+    first_page: usize,
+    pages: usize,
+    words: usize,
+}
+
+impl Log {
+    /// # Panics
+    /// If there are fewer than two pages, as then the newest record would be
+    ///  erased to make room for the next.
+    pub const fn new(first_page: usize, pages: usize, words: usize) -> Self {
+        assert!(pages >= 2, "a log needs at least two pages");
+        Log { first_page, pages, words }
+    }
+
+    /// The newest record that's intact, copied into `words`, or `false` if
+    ///  there isn't one.
+    pub fn read<F: Flash>(&self, flash: &F, words: &mut [u32]) -> bool {
+        let Some((slot, _)) = self.newest(flash) else { return false };
+
+        let address = self.address::<F>(slot);
+        for (i, word) in words.iter_mut().take(self.words).enumerate() {
+            *word = flash.read(address + 4 * (2 + i));
+        }
+
+        true
+    }
+
+    /// Adds a record of `words` after the newest, unless it's the same as the
+    ///  newest already.
+    pub fn write<F: Flash>(&self, flash: &mut F, words: &[u32]) {
+        let words = &words[..self.words];
+
+        let (mut slot, sequence) = match self.newest(flash) {
+            Some((slot, _)) if self.holds(flash, slot, words) => return,
+            Some((slot, sequence)) => ((slot + 1) % self.slots::<F>(), sequence.wrapping_add(1)),
+            None => (0, 0),
+        };
+
+        // Slots left over from a write that was cut short can't be written
+        //  again until their page is erased, so skip past them.
+        for _ in 0..self.slots::<F>() {
+            if slot % self.slots_per_page::<F>() == 0 {
+                flash.erase(self.first_page + slot / self.slots_per_page::<F>());
+                break;
+            }
+            if self.blank(flash, slot) {
+                break;
+            }
+            slot = (slot + 1) % self.slots::<F>();
+        }
+
+        let address = self.address::<F>(slot);
+        flash.write(address, MAGIC);
+        flash.write(address + 4, sequence);
+        for (i, word) in words.iter().enumerate() {
+            flash.write(address + 4 * (2 + i), *word);
+        }
+        flash.write(address + 4 * (2 + self.words), crc_words(core::iter::once(sequence).chain(words.iter().copied())));
+        flash.flush();
+    }
+
+    /// Which slot holds the newest intact record, and its sequence number.
+    fn newest<F: Flash>(&self, flash: &F) -> Option<(usize, u32)> {
+        let mut newest: Option<(usize, u32)> = None;
+
+        for slot in 0..self.slots::<F>() {
+            let address = self.address::<F>(slot);
+            if flash.read(address) != MAGIC {
+                continue;
+            }
+
+            let sequence = flash.read(address + 4);
+            let words = (0..self.words).map(|i| flash.read(address + 4 * (2 + i)));
+            if crc_words(core::iter::once(sequence).chain(words)) != flash.read(address + 4 * (2 + self.words)) {
+                continue;
+            }
+
+            if newest.is_none_or(|(_, newest)| sequence > newest) {
+                newest = Some((slot, sequence));
+            }
+        }
+
+        newest
+    }
+
+    fn holds<F: Flash>(&self, flash: &F, slot: usize, words: &[u32]) -> bool {
+        let address = self.address::<F>(slot);
+        words.iter().enumerate().all(|(i, word)| flash.read(address + 4 * (2 + i)) == *word)
+    }
+
+    fn blank<F: Flash>(&self, flash: &F, slot: usize) -> bool {
+        let address = self.address::<F>(slot);
+        (0..self.record_words()).all(|i| flash.read(address + 4 * i) == ERASED)
+    }
+
+    const fn record_words(&self) -> usize {
+        self.words + 3
+    }
+
+    const fn slots_per_page<F: Flash>(&self) -> usize {
+        F::PAGE_SIZE / (4 * self.record_words())
+    }
+
+    const fn slots<F: Flash>(&self) -> usize {
+        self.pages * self.slots_per_page::<F>()
+    }
+
+    const fn address<F: Flash>(&self, slot: usize) -> usize {
+        let page = self.first_page + slot / self.slots_per_page::<F>();
+        page * F::PAGE_SIZE + (slot % self.slots_per_page::<F>()) * 4 * self.record_words()
+    }
+}
+
+
+/// Everything chosen on the board that's worth keeping.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq)]
+pub struct Settings {
+    pub automaton: Automaton,
+    pub speed: Speed,
+    pub boundary: Boundary,
+    pub soup: Soup,
+    pub reseed: Reseed,
+}
+
+impl Settings {
+    /// How many words the settings take in flash.
+    pub const WORDS: usize = 3;
+
+    pub fn to_words(&self) -> [u32; Self::WORDS] {
+        let (kind, states, elementary, rule) = match self.automaton {
+            Automaton::Generations(generations) => (0, generations.states, 0, generations.rule),
+            Automaton::Wireworld => (1, 0, 0, Rule { birth: 0, survival: 0 }),
+            Automaton::Elementary(elementary) => (2, 0, elementary.rule, Rule { birth: 0, survival: 0 }),
+        };
+
+        [
+            kind | (states as u32) << 8 | (elementary as u32) << 16 | (self.reseed.index() as u32) << 24,
+            rule.birth as u32 | (rule.survival as u32) << 16,
+            self.speed.index() as u32
+                | (index_of(Boundary::Torus, Boundary::next, self.boundary) as u32) << 8
+                | (index_of(Pattern::Uniform, Pattern::next, self.soup.pattern) as u32) << 16
+                | (self.soup.density as u32) << 24,
+        ]
+    }
+
+    /// The settings in `words`, or `None` if they don't make sense.
+    pub fn from_words(words: [u32; Self::WORDS]) -> Option<Settings> {
+        let [automaton, rule, options] = words;
+        let byte = |word: u32, n: u32| (word >> (8 * n)) as u8;
+
+        let reseed = *Reseed::ALL.get(byte(automaton, 3) as usize)?;
+        let rule = Rule { birth: rule as u16, survival: (rule >> 16) as u16 };
+        let automaton = match byte(automaton, 0) {
+            0 if byte(automaton, 1) >= 2 && rule.birth >> 9 == 0 && rule.survival >> 9 == 0 => {
+                Automaton::Generations(Generations { rule, states: byte(automaton, 1) })
+            },
+            1 => Automaton::Wireworld,
+            2 => Automaton::Elementary(Elementary::new(byte(automaton, 2))),
+            _ => return None,
+        };
+
+        let density = byte(options, 3);
+        if !(Soup::MIN_DENSITY..=Soup::MAX_DENSITY).contains(&density) {
+            return None;
+        }
+
+        Some(Settings {
+            automaton,
+            speed: *Speed::ALL.get(byte(options, 0) as usize)?,
+            boundary: nth(Boundary::Torus, Boundary::next, byte(options, 1))?,
+            soup: Soup::new(nth(Pattern::Uniform, Pattern::next, byte(options, 2))?, density),
+            reseed,
+        })
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            automaton: Automaton::Generations(Generations::from_rule(rule::LIFE)),
+            speed: Speed::default(),
+            boundary: Boundary::default(),
+            soup: Soup::default(),
+            reseed: Reseed::default(),
+        }
+    }
+}
+
+/// How many steps of `next` it takes to get from `first` to `value`, for
+///  enums that cycle through every variant.
+fn index_of<T: Copy + PartialEq>(first: T, next: fn(T) -> T, value: T) -> u8 {
+    let mut at = first;
+    let mut index = 0;
+    while at != value {
+        at = next(at);
+        index += 1;
+    }
+
+    index
+}
+
+/// The `n`th value from `first`, or `None` if `n` goes past the last one
+///  and back round.
+fn nth<T: Copy + PartialEq>(first: T, next: fn(T) -> T, n: u8) -> Option<T> {
+    let mut at = first;
+    for _ in 0..n {
+        at = next(at);
+        if at == first {
+            return None;
+        }
+    }
+
+    Some(at)
+}
+
+
+/// Where a world is kept.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub enum Slot {
+    /// The world as it was last left, to pick up from after a reset.
+    Last,
+    /// One of the [`SAVED_SLOTS`] kept by hand.
+    Saved(usize),
+}
+
+/// How many worlds can be saved by hand.
+pub const SAVED_SLOTS: usize = 4;
+
+/// How many words a world takes, which is enough for a 32x32 world at two
+///  bits a cell. Cells past that aren't kept.
+pub const WORLD_WORDS: usize = 64;
+
+/// Packs `world` two bits a cell, in reading order. Cells in later states
+///  than 3, which only dying cells of Generations rules reach, are kept as 3.
+pub fn pack<C: World>(world: &C) -> [u32; WORLD_WORDS] {
+    let mut words = [0; WORLD_WORDS];
+    for (i, (row, col)) in cells::<C>().enumerate() {
+        words[i / 16] |= (world.get(row, col).min(3) as u32) << (2 * (i % 16));
+    }
+
+    words
+}
+
+pub fn unpack<C: World>(words: &[u32; WORLD_WORDS]) -> C {
+    let mut world = C::blank();
+    for (i, (row, col)) in cells::<C>().enumerate() {
+        world.set(row, col, (words[i / 16] >> (2 * (i % 16))) as u8 & 3);
+    }
+
+    world
+}
+
+fn cells<C: World>() -> impl Iterator<Item = (usize, usize)> {
+    (0..C::HEIGHT).flat_map(|row| (0..C::WIDTH).map(move |col| (row, col))).take(16 * WORLD_WORDS)
+}
+
+
+/// The settings and worlds kept in `flash`, which needs [`Storage::PAGES`]
+///  pages.
+#[derive(Debug)]
+pub struct Storage<F: Flash> {
+    flash: F,
+}
+
+impl<F: Flash> Storage<F> {
+    /// Two pages for each log: the settings, the last world and the saved ones.
+    pub const PAGES: usize = 2 * (2 + SAVED_SLOTS);
+
+    const SETTINGS: Log = Log::new(0, 2, Settings::WORDS);
+
+    pub const fn new(flash: F) -> Self {
+        Storage { flash }
+    }
+
+    /// The settings last saved, or the defaults if there aren't any intact.
+    pub fn settings(&self) -> Settings {
+        let mut words = [0; Settings::WORDS];
+
+        if Self::SETTINGS.read(&self.flash, &mut words) {
+            Settings::from_words(words).unwrap_or_default()
+        } else {
+            Settings::default()
+        }
+    }
+
+    pub fn save_settings(&mut self, settings: &Settings) {
+        Self::SETTINGS.write(&mut self.flash, &settings.to_words());
+    }
+
+    /// The world kept in `slot`, if there's one intact.
+    pub fn load<C: World>(&self, slot: Slot) -> Option<C> {
+        let mut words = [0; WORLD_WORDS];
+
+        Self::world_log(slot)?.read(&self.flash, &mut words).then(|| unpack(&words))
+    }
+
+    /// Keeps `world` in `slot`. Slots past the last are ignored.
+    pub fn save<C: World>(&mut self, slot: Slot, world: &C) {
+        if let Some(log) = Self::world_log(slot) {
+            log.write(&mut self.flash, &pack(world));
+        }
+    }
+
+    fn world_log(slot: Slot) -> Option<Log> {
+        let index = match slot {
+            Slot::Last => 0,
+            Slot::Saved(n) if n < SAVED_SLOTS => 1 + n,
+            Slot::Saved(_) => return None,
+        };
+
+        Some(Log::new(2 + 2 * index, 2, WORLD_WORDS))
+    }
+}
+
+
+/// Flash in memory for the tests, here and in [`crate::simulation`].
+#[cfg(test)]
+pub(crate) mod memory {
+    use super::*;
+
+    /// Flash laid out like the nRF51's, counting how often each page is
+    ///  erased.
+    pub(crate) struct MemoryFlash {
+        pub words: Vec<u32>,
+        pub erases: Vec<usize>,
+    }
+
+    impl MemoryFlash {
+        pub fn new() -> Self {
+            MemoryFlash {
+                words: vec![ERASED; Storage::<Self>::PAGES * Self::PAGE_SIZE / 4],
+                erases: vec![0; Storage::<Self>::PAGES],
+            }
+        }
+    }
+
+    impl Flash for MemoryFlash {
+        const PAGE_SIZE: usize = 1024;
+
+        fn read(&self, address: usize) -> u32 {
+            self.words[address / 4]
+        }
+
+        fn erase(&mut self, page: usize) {
+            self.words[page * Self::PAGE_SIZE / 4..][..Self::PAGE_SIZE / 4].fill(ERASED);
+            self.erases[page] += 1;
+        }
+
+        fn write(&mut self, address: usize, word: u32) {
+            self.words[address / 4] &= word;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{memory::MemoryFlash, *};
+    use crate::{elementary::RULE_110, generations::BRIANS_BRAIN, grid::Grid, wireworld};
+
+    fn settings() -> Settings {
+        Settings {
+            automaton: Automaton::Generations(BRIANS_BRAIN),
+            speed: Speed::Fast,
+            boundary: Boundary::KleinBottle,
+            soup: Soup::new(Pattern::D8, 30),
+            reseed: Reseed::Never,
+        }
+    }
+
+    #[test]
+    fn crc_matches_the_check_value() {
+        assert_eq!(crc32(*b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn blank_flash_gives_the_defaults() {
+        let storage = Storage::new(MemoryFlash::new());
+
+        assert_eq!(storage.settings(), Settings::default());
+        assert_eq!(storage.load::<Grid<32, 32>>(Slot::Last), None);
+    }
+
+    #[test]
+    fn settings_survive_the_round_trip() {
+        let mut storage = Storage::new(MemoryFlash::new());
+
+        for automaton in [Automaton::Wireworld, Automaton::Elementary(RULE_110), Automaton::Generations(Generations::new("B36/S125/5"))] {
+            let settings = Settings { automaton, ..settings() };
+            storage.save_settings(&settings);
+            assert_eq!(storage.settings(), settings);
+        }
+    }
+
+    #[test]
+    fn worlds_are_kept_in_their_own_slots() {
+        let mut storage = Storage::new(MemoryFlash::new());
+        let mut circuit = Grid::<32, 32>::new();
+        wireworld::CIRCUITS[0].place(&mut circuit, 3, 4);
+        let mut fading = Grid::<32, 32>::new();
+        fading.set(31, 31, 5);
+
+        storage.save(Slot::Saved(1), &circuit);
+        storage.save(Slot::Last, &fading);
+        storage.save(Slot::Saved(SAVED_SLOTS), &circuit);
+
+        assert_eq!(storage.load(Slot::Saved(1)), Some(circuit));
+        assert_eq!(storage.load::<Grid<32, 32>>(Slot::Last).map(|world| world[(31, 31)]), Some(3));
+        assert_eq!(storage.load::<Grid<32, 32>>(Slot::Saved(0)), None);
+        assert_eq!(storage.load::<Grid<32, 32>>(Slot::Saved(SAVED_SLOTS)), None);
+    }
+
+    #[test]
+    fn pages_wear_evenly() {
+        let mut storage = Storage::new(MemoryFlash::new());
+        let mut world = Grid::<32, 32>::new();
+
+        for i in 0..300 {
+            world.set(i % 32, i / 32, 1);
+            storage.save(Slot::Last, &world);
+            assert_eq!(storage.load(Slot::Last), Some(world));
+        }
+
+        let erases = &storage.flash.erases[2..4];
+        assert!(erases[0].abs_diff(erases[1]) <= 1);
+        // Three records fit in a page, so a page is erased every sixth save.
+        assert_eq!(erases[0] + erases[1], 100);
+    }
+
+    #[test]
+    fn saving_the_same_again_writes_nothing() {
+        let mut storage = Storage::new(MemoryFlash::new());
+        storage.save_settings(&settings());
+        let before = storage.flash.words.clone();
+
+        storage.save_settings(&settings());
+
+        assert_eq!(storage.flash.words, before);
+    }
+
+    #[test]
+    fn corrupt_records_fall_back() {
+        let mut storage = Storage::new(MemoryFlash::new());
+        storage.save_settings(&Settings::default());
+        storage.save_settings(&settings());
+
+        // Flip a bit of the newest, which leaves the one before.
+        storage.flash.words[Settings::WORDS + 3 + 2] ^= 1;
+        assert_eq!(storage.settings(), Settings::default());
+
+        storage.flash.words[2] ^= 1;
+        assert_eq!(storage.settings(), Settings::default());
+        // With nothing intact, the next save starts over without clashing.
+        storage.save_settings(&settings());
+        assert_eq!(storage.settings(), settings());
+    }
+
+    #[test]
+    fn torn_writes_are_skipped() {
+        let mut storage = Storage::new(MemoryFlash::new());
+        storage.save_settings(&Settings::default());
+
+        // Power lost just after starting the second record.
+        storage.flash.write(4 * (Settings::WORDS + 3), MAGIC);
+        assert_eq!(storage.settings(), Settings::default());
+
+        storage.save_settings(&settings());
+        assert_eq!(storage.settings(), settings());
+    }
+}
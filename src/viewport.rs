@@ -0,0 +1,75 @@
+use crate::{grid::Grid, world::World};
+
+
+/// A window onto a larger world, given by the cell shown in its top left.
+///
+/// Windows past the edge of the world wrap around, just like the world does.
+#[derive(Clone, Copy, Debug, defmt::Format, Default, PartialEq, Eq)]
+pub struct Viewport {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Viewport {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Viewport { row, col }
+    }
+
+    /// Moves the window across a `C` world, wrapping around at its edges.
+    pub fn pan<C: World>(&mut self, rows: isize, cols: isize) {
+        self.row = (self.row as isize + rows).rem_euclid(C::HEIGHT as isize) as usize;
+        self.col = (self.col as isize + cols).rem_euclid(C::WIDTH as isize) as usize;
+    }
+
+    /// Copies the part of `world` under the window into a `W` by `H` grid.
+    pub fn view<C: World, const W: usize, const H: usize>(&self, world: &C) -> Grid<W, H> {
+        let mut result = Grid::new();
+
+        for row in 0..H {
+            for col in 0..W {
+                result[(row, col)] = world.get_wrapped((self.row + row) as isize, (self.col + col) as isize);
+            }
+        }
+
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::BitGrid;
+
+    #[test]
+    fn view_copies_the_window() {
+        let mut world = BitGrid::<32, 32>::new();
+        world.set(10, 20, 1);
+        world.set(14, 24, 1);
+
+        let view: Grid<5, 5> = Viewport::new(10, 20).view(&world);
+
+        assert_eq!(view[(0, 0)], 1);
+        assert_eq!(view[(4, 4)], 1);
+        assert_eq!(view.iter().filter(|cell| **cell != 0).count(), 2);
+    }
+
+    #[test]
+    fn view_wraps_past_the_edge() {
+        let mut world = BitGrid::<32, 32>::new();
+        world.set(0, 0, 1);
+
+        let view: Grid<5, 5> = Viewport::new(30, 31).view(&world);
+
+        assert_eq!(view[(2, 1)], 1);
+    }
+
+    #[test]
+    fn pan_wraps_around_the_world() {
+        let mut viewport = Viewport::new(0, 30);
+
+        viewport.pan::<BitGrid<32, 16>>(-1, 3);
+
+        assert_eq!(viewport, Viewport::new(15, 1));
+    }
+}
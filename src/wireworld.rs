@@ -0,0 +1,151 @@
+//! Wireworld, where electrons run along wires, for building logic circuits.
+
+use crate::world::World;
+
+
+pub const EMPTY: u8 = 0;
+pub const HEAD: u8 = 1;
+pub const TAIL: u8 = 2;
+pub const CONDUCTOR: u8 = 3;
+
+/// An electron's head becomes its tail, and the tail turns back into plain
+///  wire. A wire lights up with a new head when one or two of its neighbours
+///  are heads, which is what keeps electrons going one way.
+pub fn wireworld_transitions(center_cell: u8, neighbors: &[u8]) -> u8 {
+    match center_cell {
+        HEAD => TAIL,
+        TAIL => CONDUCTOR,
+        CONDUCTOR => {
+            let head_count = neighbors.iter().filter(|n| **n == HEAD).count();
+
+            if (1..=2).contains(&head_count) { HEAD } else { CONDUCTOR }
+        },
+        _ => EMPTY,
+    }
+}
+
+
+/// A small circuit drawn with `.` for empty space, `#` for wire, and `H` and
+///  `t` for the head and tail of an electron.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Circuit {
+    pub name: &'static str,
+    pub rows: &'static [&'static str],
+}
+
+/// One electron going round a loop, which pulses every twelve ticks.
+pub const CLOCK: Circuit = Circuit {
+    name: "Clock",
+    rows: &[
+        ".###.",
+        "#...#",
+        "#...#",
+        "#...#",
+        ".tH#.",
+    ],
+};
+
+/// A clock whose pulses are split between a diode, which lets them through,
+///  and one the wrong way round, which stops them.
+pub const DIODES: Circuit = Circuit {
+    name: "Diodes",
+    rows: &[
+        ".............##.......",
+        "........######.#######",
+        "........#....##.......",
+        ".###....#.............",
+        "#...#...#.............",
+        "#...#####.............",
+        "#...#...#.............",
+        ".tH#....#.............",
+        "........#.....##......",
+        "........######.#######",
+        "..............##......",
+    ],
+};
+
+pub const CIRCUITS: [Circuit; 2] = [CLOCK, DIODES];
+
+impl Circuit {
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Draws the circuit onto `world` with its top left at `row`, `col`,
+    ///  wrapping around the edges. Cells under empty space are cleared.
+    pub fn place<C: World>(&self, world: &mut C, row: usize, col: usize) {
+        for (row_offset, line) in self.rows.iter().enumerate() {
+            for (col_offset, cell) in line.bytes().enumerate() {
+                let state = match cell {
+                    b'H' => HEAD,
+                    b't' => TAIL,
+                    b'#' => CONDUCTOR,
+                    _ => EMPTY,
+                };
+
+                world.set((row + row_offset) % C::HEIGHT, (col + col_offset) % C::WIDTH, state);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{engine::update_automata, grid::Grid, Boundary, Neighborhood};
+
+    fn step<C: World>(world: &C) -> C {
+        update_automata(world, Boundary::Dead, Neighborhood::Moore, wireworld_transitions)
+    }
+
+    #[test]
+    fn electrons_move_along_wires() {
+        let mut wire = Grid::from_rows([[TAIL, HEAD, CONDUCTOR, CONDUCTOR]]);
+
+        wire = step(&wire);
+        assert_eq!(wire.into_rows(), [[CONDUCTOR, TAIL, HEAD, CONDUCTOR]]);
+
+        wire = step(&wire);
+        assert_eq!(wire.into_rows(), [[CONDUCTOR, CONDUCTOR, TAIL, HEAD]]);
+    }
+
+    #[test]
+    fn clock_has_period_twelve() {
+        let mut clock = Grid::<5, 5>::new();
+        CLOCK.place(&mut clock, 0, 0);
+
+        let start = clock;
+        for tick in 1..=12 {
+            clock = step(&clock);
+            assert_eq!(clock == start, tick == 12);
+        }
+    }
+
+    #[test]
+    fn diodes_only_conduct_one_way() {
+        let mut world = Grid::<22, 11>::new();
+        DIODES.place(&mut world, 0, 0);
+
+        let (mut forward, mut reverse) = (0, 0);
+        for _ in 0..120 {
+            world = step(&world);
+            forward += (world[(1, 21)] == HEAD) as usize;
+            reverse += (world[(9, 21)] == HEAD) as usize;
+        }
+
+        assert!(forward >= 8);
+        assert_eq!(reverse, 0);
+    }
+
+    #[test]
+    fn circuits_are_rectangular() {
+        for circuit in CIRCUITS {
+            assert!(circuit.rows.iter().all(|row| row.len() == circuit.width()), "{}", circuit.name);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Anything the engine can step: a fixed-size rectangle of cell states.
+
+use crate::{boundary::Boundary, neighborhood::Neighborhood};
+
+/// A `WIDTH` by `HEIGHT` rectangle of cells, addressed by `(row, col)`.
+pub trait World: Sized {
+    const WIDTH: usize;
+    const HEIGHT: usize;
+
+    /// A world with every cell dead.
+    fn blank() -> Self;
+
+    fn get(&self, row: usize, col: usize) -> u8;
+
+    fn set(&mut self, row: usize, col: usize, state: u8);
+
+    /// The cell at `row`, `col`, wrapping around both axes.
+    fn get_wrapped(&self, row: isize, col: isize) -> u8 {
+        self.get(
+            row.rem_euclid(Self::HEIGHT as isize) as usize,
+            col.rem_euclid(Self::WIDTH as isize) as usize,
+        )
+    }
+
+    /// The cell at `row`, `col`, which may lie past the edge of the world.
+    ///  Cells that fall off the edge are dead.
+    fn get_beyond(&self, row: isize, col: isize, boundary: Boundary) -> u8 {
+        match boundary.resolve(row, col, Self::WIDTH, Self::HEIGHT) {
+            Some((row, col)) => self.get(row, col),
+            None => 0,
+        }
+    }
+
+    /// The states of a cell's neighbours, written to the front of `buffer`,
+    ///  in the order given by [`Neighborhood::offsets`].
+    fn neighbors<'a>(
+        &self,
+        row: usize,
+        col: usize,
+        boundary: Boundary,
+        neighborhood: Neighborhood,
+        buffer: &'a mut [u8; Neighborhood::MAX_NEIGHBORS],
+    ) -> &'a [u8] {
+        // Neighbours are at most two cells away, so away from the edges they
+        //  can be read straight off without asking the boundary.
+        let inside =
+            (2..Self::HEIGHT.saturating_sub(2)).contains(&row) && (2..Self::WIDTH.saturating_sub(2)).contains(&col);
+
+        let mut count = 0;
+        for (row_offset, col_offset) in neighborhood.offsets() {
+            let (row, col) = (row as isize + row_offset, col as isize + col_offset);
+            buffer[count] = if inside {
+                self.get(row as usize, col as usize)
+            } else {
+                self.get_beyond(row, col, boundary)
+            };
+            count += 1;
+        }
+
+        &buffer[..count]
+    }
+}
+
+
+/// The smallest rectangle holding every cell of a world that isn't dead.
+#[derive(Clone, Copy, Debug, defmt::Format, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub row: usize,
+    pub col: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl Bounds {
+    /// The bounds of `world`, or `None` if every cell is dead. Patterns that
+    ///  wrap around an edge are taken to stretch across the whole world.
+    pub fn of<C: World>(world: &C) -> Option<Bounds> {
+        let (mut top, mut left, mut bottom, mut right) = (usize::MAX, usize::MAX, 0, 0);
+
+        for row in 0..C::HEIGHT {
+            for col in 0..C::WIDTH {
+                if world.get(row, col) != 0 {
+                    top = top.min(row);
+                    left = left.min(col);
+                    bottom = bottom.max(row);
+                    right = right.max(col);
+                }
+            }
+        }
+
+        (top != usize::MAX).then(|| Bounds { row: top, col: left, height: bottom - top + 1, width: right - left + 1 })
+    }
+}
+
+
+/// A two-state world packing a row into the bits of a `u64`, so a 64x64 world
+///  takes 512 bytes instead of 4K. `W` can be at most 64.
+///
+/// Any non-zero state written to a cell is stored as `1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BitGrid<const W: usize, const H: usize> {
+    rows: [u64; H],
+}
+
+impl<const W: usize, const H: usize> BitGrid<W, H> {
+    const FITS: () = assert!(W <= 64, "a BitGrid row is packed into a u64");
+
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::FITS;
+
+        BitGrid { rows: [0; H] }
+    }
+
+    /// The cells of `row`, with column 0 in the least significant bit.
+    pub const fn row_bits(&self, row: usize) -> u64 {
+        self.rows[row]
+    }
+}
+
+impl<const W: usize, const H: usize> Default for BitGrid<W, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize, const H: usize> World for BitGrid<W, H> {
+    const WIDTH: usize = W;
+    const HEIGHT: usize = H;
+
+    fn blank() -> Self {
+        Self::new()
+    }
+
+    fn get(&self, row: usize, col: usize) -> u8 {
+        ((self.rows[row] >> col) & 1) as u8
+    }
+
+    fn set(&mut self, row: usize, col: usize, state: u8) {
+        if state != 0 {
+            self.rows[row] |= 1 << col;
+        } else {
+            self.rows[row] &= !(1 << col);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_fit_the_live_cells() {
+        let mut world = BitGrid::<8, 8>::new();
+        assert_eq!(Bounds::of(&world), None);
+
+        world.set(2, 5, 1);
+        world.set(6, 3, 1);
+        assert_eq!(Bounds::of(&world), Some(Bounds { row: 2, col: 3, height: 5, width: 3 }));
+    }
+
+    #[test]
+    fn bit_grid_stores_one_bit_per_cell() {
+        let mut world = BitGrid::<64, 2>::new();
+        world.set(1, 63, 1);
+        world.set(1, 0, 5);
+        world.set(1, 0, 0);
+
+        assert_eq!(world.get(1, 63), 1);
+        assert_eq!(world.get(1, 0), 0);
+        assert_eq!(world.row_bits(1), 1 << 63);
+        assert_eq!(world.row_bits(0), 0);
+    }
+
+    #[test]
+    fn bit_grid_neighbors_wrap_around_the_edges() {
+        let mut world = BitGrid::<40, 40>::new();
+        world.set(39, 39, 1);
+        world.set(0, 1, 1);
+
+        let mut buffer = [0; Neighborhood::MAX_NEIGHBORS];
+
+        assert_eq!(world.neighbors(0, 0, Boundary::Torus, Neighborhood::Moore, &mut buffer), [1, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(world.neighbors(0, 0, Boundary::Dead, Neighborhood::Moore, &mut buffer), [0, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(world.neighbors(0, 0, Boundary::Torus, Neighborhood::VonNeumann, &mut buffer), [0, 0, 1, 0]);
+    }
+}